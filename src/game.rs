@@ -25,8 +25,8 @@ impl Object{
         Self{position, entity, rigid}
     }
 
-    fn update(&mut self, render_scene: &mut renderer::Scene, world: &mut physics::PhysicsWorld) {
-        self.position = *world.get(self.rigid).unwrap().get_position();
+    fn update(&mut self, render_scene: &mut renderer::Scene, world: &mut physics::PhysicsWorld, alpha: Float) {
+        self.position = world.get(self.rigid).unwrap().get_interpolated_position(alpha);
         render_scene.get_entity_mut(self.entity).unwrap().position = self.position;
     }
 }
@@ -84,7 +84,7 @@ impl Game{
         // ground
         let model = resource.get_model_json("white_cube.json").await.unwrap();
         let position = Isometry3::translation(0.0, 0.0, 0.0) * Isometry3::rotation(Vector3::new(0.0, 0.0, 0.0));
-        let mut rigid_body = physics::RigidBody::new(physics::ShapeType::Cube(physics::Cube::new(Vector3::new(1000.0, 1.0, 1000.0))), 0.0);
+        let mut rigid_body = physics::RigidBody::new(physics::ShapeType::Plane(physics::Plane::new(Vector3::y_axis(), 1.0)), 0.0);
         rigid_body.set_position(&position);
         let mut entity = renderer::Entity::new(model.clone());
         entity.position = position;
@@ -142,8 +142,8 @@ impl Game{
 
     fn update(&mut self) {
         let dt = self.wait(1.0 / Self::FPS);
-        self.rigid_scene.update(dt);
-        self.objects.iter_mut().for_each(|o| o.update(&mut self.render_scene, &mut self.rigid_scene));
+        let alpha = self.rigid_scene.step(dt);
+        self.objects.iter_mut().for_each(|o| o.update(&mut self.render_scene, &mut self.rigid_scene, alpha));
     }
 
     fn resize(&mut self, width: u32, height: u32){
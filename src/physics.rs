@@ -1,9 +1,26 @@
+mod ray;
 mod rigid_body;
 mod shape;
+mod solver;
+mod toi;
+mod vehicle;
 mod world;
 
 mod bvh;
+mod character_controller;
+mod closest_points;
 mod contact;
+mod distance_constraint;
+mod event;
+mod gjk;
 mod intersect;
+mod island;
+mod joint;
+mod material;
+use self::closest_points::*;
 use self::contact::*;
-pub use self::{bvh::*, rigid_body::*, shape::*, world::*};
+use self::intersect::*;
+pub use self::{
+    bvh::*, character_controller::*, distance_constraint::*, event::*, gjk::*, island::*, joint::*,
+    material::*, ray::*, rigid_body::*, shape::*, solver::*, toi::*, vehicle::*, world::*,
+};
@@ -26,6 +26,8 @@ pub trait BoundingVolume: Clone + Copy + PartialEq {
     fn expand_mut(&mut self, p: &Point3);
     fn merge(&self, p: &Self) -> Self;
     fn volume(&self) -> f32;
+    /// Returns true if the ray hits the volume within max_toi.
+    fn intersect_ray(&self, origin: &Point3, dir: &Vector3, max_toi: Float) -> bool;
 }
 
 pub trait BuildBoundingVolume<V: BoundingVolume> {
@@ -54,7 +56,7 @@ struct BVHInternal<V> {
     bounding: V,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BVH<V, T> {
     root: Option<BVHNodeId>,
     leaves: ga::Arena<BVHLeaf<V, T>>,
@@ -250,6 +252,63 @@ impl<V: BoundingVolume, T> BVH<V, T> {
             },
         }
     }
+    /// Stores data of every leaf overlapping with the bounding volume.
+    pub fn query<'a, 'b: 'a, S: Storage<&'a T>>(&'b self, bounding: &V, storage: &mut S) {
+        if let Some(id) = self.root {
+            self.query_node(id, bounding, storage);
+        }
+    }
+    fn query_node<'a, 'b: 'a, S: Storage<&'a T>>(
+        &'b self,
+        id: BVHNodeId,
+        bounding: &V,
+        storage: &mut S,
+    ) {
+        match id {
+            BVHNodeId::Leaf(idx) => {
+                let leaf = self.get_leaf(idx).unwrap();
+                if leaf.bounding.intersect(bounding) {
+                    storage.store(&leaf.data);
+                }
+            }
+            BVHNodeId::Internal(idx) => {
+                let node = self.get_internal(idx).unwrap();
+                if node.bounding.intersect(bounding) {
+                    self.query_node(node.left_child, bounding, storage);
+                    self.query_node(node.right_child, bounding, storage);
+                }
+            }
+        }
+    }
+
+    /// Stores data of every leaf hit by the ray within max_toi.
+    pub fn query_ray<'a, 'b: 'a, S: Storage<&'a T>>(
+        &'b self,
+        origin: &Point3,
+        dir: &Vector3,
+        max_toi: Float,
+        storage: &mut S,
+    ) {
+        let mut stack: Vec<BVHNodeId> = self.root.into_iter().collect();
+        while let Some(id) = stack.pop() {
+            if !self
+                .get_bounding_volume(id)
+                .unwrap()
+                .intersect_ray(origin, dir, max_toi)
+            {
+                continue;
+            }
+            match id {
+                BVHNodeId::Leaf(idx) => storage.store(&self.get_leaf(idx).unwrap().data),
+                BVHNodeId::Internal(idx) => {
+                    let node = self.get_internal(idx).unwrap();
+                    stack.push(node.left_child);
+                    stack.push(node.right_child);
+                }
+            }
+        }
+    }
+
     pub fn get_overlaps<'a, 'b: 'a, S: Storage<(&'a T, &'a T)>>(&'b self, storage: &mut S) {
         match self.root {
             Some(id) => self.get_overlaps_node(id, storage),
@@ -264,8 +323,11 @@ impl<V: BoundingVolume, T> BVH<V, T> {
         match id {
             BVHNodeId::Leaf(_) => (),
             BVHNodeId::Internal(idx) => {
+                // overlaps between the children, then overlaps inside each child.
                 let node = self.get_internal(idx).unwrap();
                 self.get_overlaps_pair(node.left_child, node.right_child, storage);
+                self.get_overlaps_node(node.left_child, storage);
+                self.get_overlaps_node(node.right_child, storage);
             }
         }
     }
@@ -292,7 +354,6 @@ impl<V: BoundingVolume, T> BVH<V, T> {
                     self.get_overlaps_pair(id1, internal.left_child, storage);
                     self.get_overlaps_pair(id1, internal.right_child, storage);
                 }
-                self.get_overlaps_pair(internal.left_child, internal.right_child, storage);
             }
             (BVHNodeId::Internal(iidx), BVHNodeId::Leaf(lidx)) => {
                 let (leaf, internal) = (
@@ -303,7 +364,6 @@ impl<V: BoundingVolume, T> BVH<V, T> {
                     self.get_overlaps_pair(internal.left_child, id2, storage);
                     self.get_overlaps_pair(internal.right_child, id2, storage);
                 }
-                self.get_overlaps_pair(internal.left_child, internal.right_child, storage);
             }
             (BVHNodeId::Internal(idx1), BVHNodeId::Internal(idx2)) => {
                 let (i1, i2) = (
@@ -319,8 +379,6 @@ impl<V: BoundingVolume, T> BVH<V, T> {
                         self.get_overlaps_pair(id1, i2.right_child, storage);
                     }
                 }
-                self.get_overlaps_pair(i1.left_child, i1.right_child, storage);
-                self.get_overlaps_pair(i2.left_child, i2.right_child, storage);
             }
         }
     }
@@ -525,4 +583,24 @@ mod test {
             assert_eq!(iter.next(), None);
         }
     }
+    #[test]
+    fn test_bvh_query() {
+        let mut bvh = BVH::new();
+        for i in 0..10u32 {
+            bvh.insert(
+                i,
+                AABB::new(
+                    Point3::new(i as Float * 2.0, 0.0, 0.0),
+                    Vector3::new(0.5, 0.5, 0.5),
+                ),
+            );
+        }
+        let mut found = Vec::new();
+        bvh.query(
+            &AABB::new(Point3::new(5.0, 0.0, 0.0), Vector3::new(2.0, 1.0, 1.0)),
+            &mut found,
+        );
+        found.sort();
+        assert_eq!(found, vec![&2, &3]);
+    }
 }
@@ -16,6 +16,31 @@ impl AABB {
         let radii = (max - min) * 0.5;
         Self { center, radii }
     }
+
+    /// Returns the distances at which the ray enters and leaves the box,
+    /// or None if it misses it. The ray starts at 0, so the entry is never negative.
+    pub fn clip_ray(&self, origin: &Point3, dir: &Vector3) -> Option<(Float, Float)> {
+        let (mut enter, mut exit) = (0.0, FLOAT_MAX);
+        for i in 0..3 {
+            let (min, max) = (
+                self.center[i] - self.radii[i],
+                self.center[i] + self.radii[i],
+            );
+            if dir[i].abs() < 1.0e-7 {
+                if origin[i] < min || origin[i] > max {
+                    return None;
+                }
+                continue;
+            }
+            let (t1, t2) = ((min - origin[i]) / dir[i], (max - origin[i]) / dir[i]);
+            enter = t1.min(t2).max(enter);
+            exit = t1.max(t2).min(exit);
+            if enter > exit {
+                return None;
+            }
+        }
+        Some((enter, exit))
+    }
 }
 
 impl BoundingVolume for AABB {
@@ -97,6 +122,11 @@ impl BoundingVolume for AABB {
     fn volume(&self) -> f32 {
         self.radii.x * self.radii.y * self.radii.z
     }
+
+    fn intersect_ray(&self, origin: &Point3, dir: &Vector3, max_toi: Float) -> bool {
+        self.clip_ray(origin, dir)
+            .is_some_and(|(enter, _)| enter <= max_toi)
+    }
 }
 
 #[cfg(test)]
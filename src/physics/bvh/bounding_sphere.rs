@@ -57,6 +57,13 @@ impl BoundingVolume for BoundingSphere {
     fn volume(&self) -> Float {
         self.radius
     }
+
+    fn intersect_ray(&self, origin: &Point3, dir: &Vector3, max_toi: Float) -> bool {
+        // distance from the center to the closest point of the ray segment.
+        let offset = self.center - origin;
+        let toi = (offset.dot(dir) / dir.magnitude_squared()).clamp(0.0, max_toi);
+        (offset - dir * toi).magnitude_squared() <= self.radius * self.radius
+    }
 }
 
 #[cfg(test)]
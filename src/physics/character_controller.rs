@@ -0,0 +1,383 @@
+use super::*;
+use crate::math::*;
+
+/// Gap kept between the character and what it touches, so it can slide without getting stuck.
+const SKIN_WIDTH: Float = 0.01;
+
+/// Number of surfaces a move can slide along.
+const MAX_SLIDE_ITERATIONS: usize = 4;
+
+/// Moves a body by sweeping its shape through the world, sliding along what it hits.
+/// The body is usually a kinematic capsule or sphere. A kinematic body is moved through
+/// its kinematic target, so that it pushes the dynamic bodies in its way in the next update,
+/// other bodies are moved directly.
+#[derive(Debug, Clone)]
+pub struct CharacterController {
+    body: RigidBodyId,
+    up: UnitVector3,
+    /// Highest step the character climbs without jumping.
+    max_step_height: Float,
+    /// Steepest slope the character walks up, in radians.
+    max_slope_angle: Float,
+    /// Distance the character is pulled down to stay on the ground, walking down slopes and steps.
+    snap_distance: Float,
+    grounded: bool,
+    ground_normal: Option<UnitVector3>,
+}
+
+impl CharacterController {
+    pub fn new(body: RigidBodyId) -> Self {
+        Self {
+            body,
+            up: Vector3::y_axis(),
+            max_step_height: 0.3,
+            max_slope_angle: PI / 4.0,
+            snap_distance: 0.2,
+            grounded: false,
+            ground_normal: None,
+        }
+    }
+
+    pub fn get_body(&self) -> RigidBodyId {
+        self.body
+    }
+    pub fn get_up(&self) -> &UnitVector3 {
+        &self.up
+    }
+    pub fn set_up(&mut self, up: &UnitVector3) {
+        self.up = *up;
+    }
+    pub fn get_max_step_height(&self) -> Float {
+        self.max_step_height
+    }
+    pub fn set_max_step_height(&mut self, height: Float) {
+        self.max_step_height = height;
+    }
+    pub fn get_max_slope_angle(&self) -> Float {
+        self.max_slope_angle
+    }
+    pub fn set_max_slope_angle(&mut self, angle: Float) {
+        self.max_slope_angle = angle;
+    }
+    pub fn get_snap_distance(&self) -> Float {
+        self.snap_distance
+    }
+    pub fn set_snap_distance(&mut self, distance: Float) {
+        self.snap_distance = distance;
+    }
+
+    /// Returns true if the character stood on walkable ground after its last move.
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+    pub fn get_ground_normal(&self) -> Option<&UnitVector3> {
+        self.ground_normal.as_ref()
+    }
+
+    /// Moves the character by the displacement, as far as the world lets it.
+    /// The part along the up axis is moved after the rest, so that gravity lands the character
+    /// after it walked. Returns the displacement it actually moved by.
+    pub fn move_character(&mut self, world: &mut PhysicsWorld, displacement: &Vector3) -> Vector3 {
+        let Some(body) = world.get(self.body) else {
+            return Vector3::zeros();
+        };
+        let start = *body.get_kinematic_target().unwrap_or(body.get_position());
+        let was_grounded = self.grounded;
+        self.grounded = false;
+        self.ground_normal = None;
+
+        let vertical = displacement.dot(&self.up);
+        let horizontal = displacement - self.up.into_inner() * vertical;
+        let position = self.slide(world, body, start, horizontal, was_grounded, false);
+        let vertical = self.up.into_inner() * vertical;
+        let mut position = self.slide(world, body, position, vertical, false, true);
+
+        // stick to the ground unless jumping.
+        if !self.grounded && displacement.dot(&self.up) <= 0.0 {
+            let distance = if was_grounded {
+                self.snap_distance
+            } else {
+                SKIN_WIDTH * 2.0
+            };
+            if let Some(hit) = self.cast(world, body, &position, &-self.up, distance) {
+                if let Some(normal) = self.ground_normal(world, &hit) {
+                    position = translated(&position, &(hit.normal.into_inner() * SKIN_WIDTH));
+                    position = translated(&position, &(-self.up.into_inner() * hit.toi));
+                    self.grounded = true;
+                    self.ground_normal = Some(normal);
+                }
+            }
+        }
+
+        let body = world.get_mut(self.body).unwrap();
+        if body.is_kinematic() {
+            body.set_kinematic_target(&position);
+        } else {
+            body.set_position(&position);
+        }
+        position.translation.vector - start.translation.vector
+    }
+
+    /// Moves the position by the motion, sliding along the surfaces it hits.
+    /// Horizontal motions climb steps if can_step is set, and don't go up steep slopes.
+    /// Vertical motions stop on walkable ground, which grounds the character.
+    fn slide(
+        &mut self,
+        world: &PhysicsWorld,
+        body: &RigidBody,
+        mut position: Isometry3,
+        mut motion: Vector3,
+        can_step: bool,
+        vertical: bool,
+    ) -> Isometry3 {
+        for _ in 0..MAX_SLIDE_ITERATIONS {
+            let distance = motion.magnitude();
+            if distance <= FLOAT_MIN_POSITIVE {
+                break;
+            }
+            let dir = UnitVector3::new_unchecked(motion / distance);
+            let Some(hit) = self.cast(world, body, &position, &dir, distance) else {
+                position = translated(&position, &motion);
+                break;
+            };
+            let normal = hit.normal.into_inner();
+            position = translated(
+                &position,
+                &(dir.into_inner() * hit.toi + normal * SKIN_WIDTH),
+            );
+            motion -= dir.into_inner() * hit.toi;
+
+            let walkable = self.is_walkable(&hit.normal);
+            if vertical {
+                if motion.dot(&self.up) < 0.0 {
+                    if let Some(ground_normal) = self.ground_normal(world, &hit) {
+                        self.grounded = true;
+                        self.ground_normal = Some(ground_normal);
+                        break;
+                    }
+                }
+                motion -= normal * motion.dot(&normal);
+            } else if walkable {
+                motion -= normal * motion.dot(&normal);
+            } else {
+                if can_step {
+                    if let Some((stepped, moved)) = self.step(world, body, &position, &motion) {
+                        position = stepped;
+                        motion -= dir.into_inner() * moved;
+                        continue;
+                    }
+                }
+                // walls only stop the horizontal motion, so steep slopes can't be climbed.
+                let wall = normal - self.up.into_inner() * normal.dot(&self.up);
+                let wall = wall.try_normalize(FLOAT_MIN_POSITIVE).unwrap_or(normal);
+                motion -= wall * motion.dot(&wall);
+            }
+        }
+        position
+    }
+
+    /// Tries to climb onto a step in front of the position, moving up, forward and down.
+    /// Returns the new position and the distance moved forward.
+    fn step(
+        &self,
+        world: &PhysicsWorld,
+        body: &RigidBody,
+        position: &Isometry3,
+        motion: &Vector3,
+    ) -> Option<(Isometry3, Float)> {
+        let height = match self.cast(world, body, position, &self.up, self.max_step_height) {
+            Some(hit) => hit.toi - SKIN_WIDTH,
+            None => self.max_step_height,
+        };
+        if height <= SKIN_WIDTH {
+            return None;
+        }
+        let raised = translated(position, &(self.up.into_inner() * height));
+
+        let distance = motion.magnitude();
+        let dir = UnitVector3::new_normalize(*motion);
+        let moved = match self.cast(world, body, &raised, &dir, distance) {
+            Some(hit) => hit.toi - SKIN_WIDTH,
+            None => distance,
+        };
+        if moved <= SKIN_WIDTH {
+            return None;
+        }
+        let advanced = translated(&raised, &(dir.into_inner() * moved));
+
+        let hit = self.cast(world, body, &advanced, &-self.up, height + SKIN_WIDTH)?;
+        self.ground_normal(world, &hit)?;
+        let landed = translated(&advanced, &(-self.up.into_inner() * (hit.toi - SKIN_WIDTH)));
+        Some((landed, moved))
+    }
+
+    /// Sweeps the character's shape, ignoring its own body, sensors and bodies it doesn't
+    /// collide with. Bodies it overlaps and moves away from don't stop it.
+    fn cast(
+        &self,
+        world: &PhysicsWorld,
+        body: &RigidBody,
+        position: &Isometry3,
+        dir: &UnitVector3,
+        distance: Float,
+    ) -> Option<ShapeHit> {
+        let mut leaving = Vec::new();
+        loop {
+            let filter = |id: RigidBodyId, other: &RigidBody| {
+                id != self.body
+                    && !other.is_sensor()
+                    && body.can_collide_with(other)
+                    && !world.is_pair_ignored(self.body, id)
+                    && !leaving.contains(&id)
+            };
+            let hit = world.cast_shape(body.get_shape(), position, dir, distance, filter)?;
+            if hit.toi > 0.0 || hit.normal.dot(dir) < 0.0 {
+                return Some(hit);
+            }
+            leaving.push(hit.body);
+        }
+    }
+
+    /// Returns the normal of the ground hit by a downward cast if the character can stand on it.
+    /// Round shapes resting on an edge hit it with a slanted normal,
+    /// so the face beyond the edge is looked at instead.
+    fn ground_normal(&self, world: &PhysicsWorld, hit: &ShapeHit) -> Option<UnitVector3> {
+        if self.is_walkable(&hit.normal) {
+            return Some(hit.normal);
+        }
+        let normal = hit.normal.into_inner();
+        let outward = normal - self.up.into_inner() * normal.dot(&self.up);
+        let inward = -outward.try_normalize(FLOAT_MIN_POSITIVE)?;
+        // high enough to be above any walkable face, which also rejects steep ones it starts in.
+        let origin = hit.point + (inward + self.up.into_inner() * 2.0) * SKIN_WIDTH;
+        let filter = |id: RigidBodyId, _: &RigidBody| id == hit.body;
+        let face = world.cast_ray(&origin, &-self.up, SKIN_WIDTH * 4.0, filter)?;
+        (face.distance > 0.0 && self.is_walkable(&face.normal)).then_some(face.normal)
+    }
+
+    fn is_walkable(&self, normal: &UnitVector3) -> bool {
+        normal.dot(&self.up) >= self.max_slope_angle.cos()
+    }
+}
+
+fn translated(position: &Isometry3, translation: &Vector3) -> Isometry3 {
+    Translation3::from(*translation) * position
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn world_with_character(x: Float) -> (PhysicsWorld, CharacterController) {
+        let mut world = PhysicsWorld::new();
+        world.insert(RigidBody::new(
+            ShapeType::Plane(Plane::new(Vector3::y_axis(), 0.0)),
+            0.0,
+        ));
+        let mut body = RigidBody::new(ShapeType::Capsule(Capsule::new(0.5, 0.5)), 1.0);
+        body.set_body_type(BodyType::Kinematic);
+        body.set_position(&Isometry3::translation(x, 1.0 + SKIN_WIDTH, 0.0));
+        let body = world.insert(body);
+        (world, CharacterController::new(body))
+    }
+
+    fn insert_box(world: &mut PhysicsWorld, position: Isometry3, half_extents: Vector3) {
+        let mut body = RigidBody::new(ShapeType::Cube(Cube::new(half_extents)), 0.0);
+        body.set_position(&position);
+        world.insert(body);
+    }
+
+    /// Walks the character with gravity for a second and returns its position.
+    fn walk(
+        world: &mut PhysicsWorld,
+        controller: &mut CharacterController,
+        velocity: Vector3,
+    ) -> Point3 {
+        for _ in 0..60 {
+            let displacement = (velocity + Vector3::new(0.0, -5.0, 0.0)) / 60.0;
+            controller.move_character(world, &displacement);
+            world.update(1.0 / 60.0);
+        }
+        let body = world.get(controller.get_body()).unwrap();
+        Point3::from(body.get_position().translation.vector)
+    }
+
+    #[test]
+    fn test_walk_and_slide() {
+        let (mut world, mut controller) = world_with_character(0.0);
+        insert_box(
+            &mut world,
+            Isometry3::translation(3.0, 1.0, 0.0),
+            Vector3::new(0.5, 1.0, 5.0),
+        );
+
+        let position = walk(&mut world, &mut controller, Vector3::new(1.0, 0.0, 0.0));
+        assert!(controller.is_grounded());
+        assert_eq!(controller.get_ground_normal(), Some(&Vector3::y_axis()));
+        assert!((position.x - 1.0).abs() < 1.0e-3);
+        assert!((position.y - 1.0).abs() < 0.02);
+
+        // against the wall, only the motion along it is kept.
+        let position = walk(&mut world, &mut controller, Vector3::new(2.0, 0.0, 2.0));
+        assert!(position.x < 2.0 && position.x > 1.95);
+        assert!((position.z - 2.0).abs() < 0.05);
+        assert!(controller.is_grounded());
+    }
+
+    #[test]
+    fn test_steps_and_slopes() {
+        let (mut world, mut controller) = world_with_character(0.0);
+        // a low step, then a high one.
+        insert_box(
+            &mut world,
+            Isometry3::translation(3.0, 0.1, 0.0),
+            Vector3::new(1.0, 0.1, 1.0),
+        );
+        insert_box(
+            &mut world,
+            Isometry3::translation(5.0, 0.5, 0.0),
+            Vector3::new(1.0, 0.5, 1.0),
+        );
+        let position = walk(&mut world, &mut controller, Vector3::new(4.0, 0.0, 0.0));
+        assert!((position.y - 1.2).abs() < 0.02);
+        assert!(position.x < 3.5 && position.x > 3.45);
+        assert!(controller.is_grounded());
+
+        // walking back down the step snaps to the ground.
+        let body = controller.get_body();
+        let mut was_grounded = true;
+        for _ in 0..60 {
+            controller.move_character(&mut world, &Vector3::new(-3.0 / 60.0, 0.0, 0.0));
+            world.update(1.0 / 60.0);
+            was_grounded &= controller.is_grounded();
+        }
+        assert!(was_grounded);
+        let y = world.get(body).unwrap().get_position().translation.y;
+        assert!((y - 1.0).abs() < 0.02);
+
+        // a ramp steeper than the max slope.
+        let (mut world, mut controller) = world_with_character(0.0);
+        let ramp = Isometry3::new(
+            Vector3::new(4.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, PI / 3.0),
+        );
+        insert_box(&mut world, ramp, Vector3::new(2.0, 0.1, 2.0));
+        let position = walk(&mut world, &mut controller, Vector3::new(5.0, 0.0, 0.0));
+        // stopped where the capsule touches the foot of the ramp, at about x = 3.6.
+        assert!(position.y < 1.1);
+        assert!(position.x < 3.7);
+
+        // a gentle one.
+        let (mut world, mut controller) = world_with_character(0.0);
+        let ramp = Isometry3::new(
+            Vector3::new(4.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, PI / 8.0),
+        );
+        insert_box(&mut world, ramp, Vector3::new(2.0, 0.1, 2.0));
+        let position = walk(&mut world, &mut controller, Vector3::new(5.0, 0.0, 0.0));
+        assert!(position.y > 1.3);
+        assert!(controller.is_grounded());
+        let normal = controller.get_ground_normal().unwrap();
+        assert!((normal.y - Float::cos(PI / 8.0)).abs() < 1.0e-3);
+    }
+}
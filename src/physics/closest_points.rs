@@ -0,0 +1,11 @@
+mod closest_points_compound_shape;
+mod closest_points_convex_convex;
+mod closest_points_height_field_shape;
+mod closest_points_plane_convex;
+mod closest_points_tri_mesh_shape;
+
+pub use self::{
+    closest_points_compound_shape::*, closest_points_convex_convex::*,
+    closest_points_height_field_shape::*, closest_points_plane_convex::*,
+    closest_points_tri_mesh_shape::*,
+};
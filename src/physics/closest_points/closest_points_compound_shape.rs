@@ -0,0 +1,32 @@
+use crate::math::*;
+use crate::physics::*;
+
+/// Returns the closest points of the closest child of the compound and the shape,
+/// or None if they're all farther apart than max_distance.
+/// child1 of the contact is the index of the child.
+/// pos12 is shape's position relative to compound.
+pub fn closest_points_compound_shape(
+    compound: &Compound,
+    shape: &ShapeType,
+    pos12: &Isometry3,
+    max_distance: Float,
+) -> Option<Contact> {
+    let mut closest: Option<Contact> = None;
+    for (i, child) in compound.children().iter().enumerate() {
+        let child_pos12 = child.position.inverse() * pos12;
+        if let Some(mut contact) = child
+            .shape
+            .closest_points(shape, &child_pos12, max_distance)
+        {
+            // convert from the child's coordinates to the compound's.
+            contact.point1 = child.position.transform_point(&contact.point1);
+            contact.normal1 = child.position.rotation * contact.normal1;
+            contact.child1 = Some(i);
+            closest = match closest {
+                Some(c) if c.separation_distance <= contact.separation_distance => Some(c),
+                _ => Some(contact),
+            };
+        }
+    }
+    closest
+}
@@ -0,0 +1,75 @@
+use crate::math::*;
+use crate::physics::*;
+
+/// Returns the closest points of two convex shapes as a contact with positive separation,
+/// or None if they're farther apart than max_distance.
+/// Intersecting shapes get their penetration contact, or a touching contact if EPA can't
+/// find the penetration of a degenerate intersection, so that they are never taken as apart.
+/// pos12 is shape2's position relative to shape1.
+pub fn closest_points_convex_convex(
+    shape1: &dyn Shape,
+    shape2: &dyn Shape,
+    pos12: &Isometry3,
+    max_distance: Float,
+) -> Option<Contact> {
+    match gjk(shape1, shape2, pos12) {
+        GjkResult::Intersection(_) => contact_convex_convex(shape1, shape2, pos12)
+            .or_else(|| Some(touching_contact(shape1, shape2, pos12))),
+        GjkResult::Separation {
+            point1,
+            point2,
+            distance,
+        } => {
+            if distance > max_distance {
+                return None;
+            }
+            let normal1 = UnitVector3::new_normalize(pos12.transform_point(&point2) - point1);
+            let normal2 = pos12.inverse_transform_unit_vector(&(-normal1));
+            Some(Contact::new(
+                point1, point2, normal1, normal2, distance, 0.0,
+            ))
+        }
+    }
+}
+
+/// Returns a contact without separation between the supporting points of the shapes
+/// along the line between their centers of mass.
+fn touching_contact(shape1: &dyn Shape, shape2: &dyn Shape, pos12: &Isometry3) -> Contact {
+    let center1 = shape1.get_center_of_mass();
+    let center2 = pos12.transform_point(&shape2.get_center_of_mass());
+    let normal1 =
+        UnitVector3::try_new(center2 - center1, FLOAT_MIN_POSITIVE).unwrap_or_else(Vector3::x_axis);
+    let normal2 = pos12.inverse_transform_unit_vector(&(-normal1));
+    let point1 = shape1.supporting_point(&normal1, 0.0);
+    let point2 = shape2.supporting_point(&normal2, 0.0);
+    Contact::new(point1, point2, normal1, normal2, 0.0, 0.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_closest_points_convex_convex() {
+        let sphere = Sphere::new(0.5);
+        let pos12 = Isometry3::translation(2.0, 0.0, 0.0);
+        let contact = closest_points_convex_convex(&sphere, &sphere, &pos12, 1.5).unwrap();
+        assert!((contact.separation_distance - 1.0).abs() < 1.0e-4);
+        assert!((contact.point1 - Point3::new(0.5, 0.0, 0.0)).magnitude() < 1.0e-4);
+        assert_eq!(
+            closest_points_convex_convex(&sphere, &sphere, &pos12, 0.5),
+            None
+        );
+
+        // flat triangles in the same plane overlap, but have no penetration for EPA to find.
+        let triangle = Triangle::new(
+            Point3::new(-1.0, 0.0, -1.0),
+            Point3::new(1.0, 0.0, -1.0),
+            Point3::new(0.0, 0.0, 1.0),
+        );
+        let pos12 = Isometry3::translation(0.5, 0.0, 0.0);
+        assert_eq!(contact_convex_convex(&triangle, &triangle, &pos12), None);
+        let contact = closest_points_convex_convex(&triangle, &triangle, &pos12, 0.0).unwrap();
+        assert_eq!(contact.separation_distance, 0.0);
+        assert!((contact.normal1.into_inner() - Vector3::x()).magnitude() < 1.0e-5);
+    }
+}
@@ -0,0 +1,32 @@
+use crate::math::*;
+use crate::physics::*;
+
+/// Returns the closest points of the height field and a convex shape,
+/// or None if they're farther apart than max_distance.
+/// pos12 is shape's position relative to height field.
+pub fn closest_points_height_field_shape(
+    field: &HeightField,
+    shape: &ShapeType,
+    pos12: &Isometry3,
+    max_distance: Float,
+) -> Option<Contact> {
+    let mut aabb = shape.build_aabb(pos12);
+    aabb.radii.add_scalar_mut(max_distance);
+    let (rows, cols) = field.cells_in(&aabb)?;
+    let mut closest: Option<Contact> = None;
+    for row in rows {
+        for col in cols.clone() {
+            for triangle in &field.cell_triangles(row, col) {
+                let contact =
+                    closest_points_convex_convex(triangle, shape.as_shape(), pos12, max_distance);
+                if let Some(contact) = contact {
+                    closest = match closest {
+                        Some(c) if c.separation_distance <= contact.separation_distance => Some(c),
+                        _ => Some(contact),
+                    };
+                }
+            }
+        }
+    }
+    closest
+}
@@ -0,0 +1,28 @@
+use crate::math::*;
+use crate::physics::*;
+
+/// Returns the point of the shape closest to the plane as a contact,
+/// or None if it's farther than max_distance.
+/// pos12 is shape's position relative to plane.
+pub fn closest_points_plane_convex(
+    plane: &Plane,
+    shape: &dyn Shape,
+    pos12: &Isometry3,
+    max_distance: Float,
+) -> Option<Contact> {
+    let normal2 = pos12.inverse_transform_unit_vector(&(-plane.normal));
+    let point2 = shape.supporting_point(&normal2, 0.0);
+    let closest = pos12.transform_point(&point2);
+    let separation_distance = plane.distance(&closest);
+    if separation_distance > max_distance {
+        return None;
+    }
+    Some(Contact::new(
+        plane.project_point(&closest),
+        point2,
+        plane.normal,
+        normal2,
+        separation_distance,
+        0.0,
+    ))
+}
@@ -0,0 +1,23 @@
+use crate::math::*;
+use crate::physics::*;
+
+/// Returns the closest points of the mesh and a convex shape,
+/// or None if they're farther apart than max_distance.
+/// pos12 is shape's position relative to mesh.
+pub fn closest_points_tri_mesh_shape(
+    mesh: &TriMesh,
+    shape: &ShapeType,
+    pos12: &Isometry3,
+    max_distance: Float,
+) -> Option<Contact> {
+    let mut aabb = shape.build_aabb(pos12);
+    aabb.radii.add_scalar_mut(max_distance);
+    let mut triangles = Vec::new();
+    mesh.query_triangles(&aabb, &mut triangles);
+    triangles
+        .into_iter()
+        .filter_map(|i| {
+            closest_points_convex_convex(&mesh.triangle(*i), shape.as_shape(), pos12, max_distance)
+        })
+        .min_by(|c1, c2| c1.separation_distance.total_cmp(&c2.separation_distance))
+}
@@ -1,10 +1,27 @@
+mod contact_capsule_sphere;
+mod contact_compound_shape;
+mod contact_convex_convex;
+mod contact_cube_capsule;
 mod contact_cube_cube;
 mod contact_cube_sphere;
+mod contact_height_field_shape;
+mod contact_plane_convex;
 mod contact_sphere_sphere;
+mod contact_tri_mesh_shape;
+mod contact_triangle_sphere;
 use super::*;
 use crate::math::*;
 
-pub use self::{contact_cube_cube::*, contact_cube_sphere::*, contact_sphere_sphere::*};
+pub use self::{
+    contact_capsule_sphere::*, contact_compound_shape::*, contact_convex_convex::*,
+    contact_cube_capsule::*, contact_cube_cube::*, contact_cube_sphere::*,
+    contact_height_field_shape::*, contact_plane_convex::*, contact_sphere_sphere::*,
+    contact_tri_mesh_shape::*, contact_triangle_sphere::*,
+};
+/// Manifolds keep points separated by less than this distance,
+/// so that resting contacts don't come and go when the bodies separate slightly.
+pub const CONTACT_MARGIN: Float = 0.02;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Contact {
     pub point1: Point3,
@@ -12,7 +29,15 @@ pub struct Contact {
     pub normal1: UnitVector3,
     pub normal2: UnitVector3,
     pub separation_distance: Float,
+    /// Fraction of the step at which the shapes touch.
+    /// 0 unless the contact was found by continuous collision detection.
     pub toi: Float,
+    /// Index of the compound child hit on each side, if the shape is a compound.
+    pub child1: Option<usize>,
+    pub child2: Option<usize>,
+    /// Identifies the features of the shapes in contact, so that a contact can be matched
+    /// across steps. Unique among the contacts of a manifold.
+    pub feature: u32,
 }
 
 impl Contact {
@@ -31,6 +56,9 @@ impl Contact {
             normal2,
             separation_distance,
             toi,
+            child1: None,
+            child2: None,
+            feature: 0,
         }
     }
     pub fn flip(&self) -> Self {
@@ -39,77 +67,19 @@ impl Contact {
             point2: self.point1,
             normal1: self.normal2,
             normal2: self.normal1,
+            child1: self.child2,
+            child2: self.child1,
             ..*self
         }
     }
 }
 
-pub fn resolve_contact(b1: &mut RigidBody, b2: &mut RigidBody, contact: &Contact) {
-    if contact.separation_distance >= 0.0 {
-        return;
-    }
-    let inv_mass1 = b1.get_inv_mass();
-    let inv_mass2 = b2.get_inv_mass();
-    if inv_mass1 == 0.0 && inv_mass2 == 0.0 {
-        return;
-    }
-    let pt1 = b1.local_to_world_point(&contact.point1);
-    let pt2 = b2.local_to_world_point(&contact.point2);
-    let elasticity = b1.get_elasticity() * b2.get_elasticity();
-
-    let inv_mass1 = b1.get_inv_mass();
-    let inv_mass2 = b2.get_inv_mass();
-
-    // Calculate collision impulse.
-
-    let inv_inertia_world1 = b1.get_inv_inertia_tensor_world();
-    let inv_inertia_world2 = b2.get_inv_inertia_tensor_world();
-
-    let normal_world = b1.local_to_world_vector(&contact.normal1);
-
-    let relative1 = pt1 - b1.get_center_of_mass_world();
-    let relative2 = pt2 - b2.get_center_of_mass_world();
+/// Storage adapter that flips contacts before storing them.
+/// Used when a manifold is computed with the shapes in the reverse order.
+pub struct Flipped<'a, S>(pub &'a mut S);
 
-    let angularj1 = (inv_inertia_world1 * relative1.cross(&normal_world)).cross(&relative1);
-    let angularj2 = (inv_inertia_world2 * relative2.cross(&normal_world)).cross(&relative2);
-    let angular_factor = (angularj1 + angularj2).dot(&normal_world);
-
-    let vel1 = b1.get_linear_velocity() + b1.get_angular_velocity().cross(&relative1);
-    let vel2 = b2.get_linear_velocity() + b2.get_angular_velocity().cross(&relative2);
-
-    let v12 = vel1 - vel2;
-    let impulsej =
-        (1.0 + elasticity) * v12.dot(&normal_world) / (inv_mass1 + inv_mass2 + angular_factor);
-    let impulse = normal_world * impulsej;
-
-    b1.apply_impulse_point_world(&(-impulse), &pt1);
-    b2.apply_impulse_point_world(&impulse, &pt2);
-
-    // Calculate friction impulse.
-    let vnorm = normal_world * normal_world.dot(&v12);
-    let vtang = v12 - vnorm;
-    if vtang.magnitude_squared() > 0.00001 {
-        let relative_vtang = vtang.normalize();
-        let friction1 = b1.get_friction();
-        let friction2 = b2.get_friction();
-        let friction = friction1 * friction2;
-
-        let inertia1 = (inv_inertia_world1 * relative1.cross(&relative_vtang)).cross(&relative1);
-        let inertia2 = (inv_inertia_world2 * relative2.cross(&relative_vtang)).cross(&relative2);
-        let inv_inertia = (inertia1 + inertia2).dot(&relative_vtang);
-
-        let reduced_mass = 1.0 / (inv_mass1 + inv_mass2 + inv_inertia);
-        let impulse_friction = vtang * reduced_mass * friction;
-
-        b1.apply_impulse_point_world(&(-impulse_friction), &pt1);
-        b2.apply_impulse_point_world(&impulse_friction, &pt2);
+impl<S: Storage<Contact>> Storage<Contact> for Flipped<'_, S> {
+    fn store(&mut self, item: Contact) {
+        self.0.store(item.flip());
     }
-
-    // Resolve penetration.
-    let denom = 1.0 / (inv_mass1 + inv_mass2);
-    let t1 = inv_mass1 * denom;
-    let t2 = inv_mass2 * denom;
-    let d = pt2 - pt1;
-    b1.append_translation(&Translation3::from(t1 * d));
-    b2.append_translation(&Translation3::from(-t2 * d));
 }
@@ -0,0 +1,43 @@
+use crate::math::*;
+use crate::physics::*;
+
+/// Returns contact information if they contact.
+/// pos12 is sphere's position relative to capsule.
+pub fn contact_capsule_sphere(
+    capsule: &Capsule,
+    sphere: &Sphere,
+    pos12: &Isometry3,
+) -> Option<Contact> {
+    // closest point on the capsule's segment to sphere's center.
+    let center = pos12.translation.vector;
+    let closest_point = Point3::new(
+        0.0,
+        center.y.clamp(-capsule.half_height, capsule.half_height),
+        0.0,
+    );
+    let offset = center - closest_point.coords;
+    let d2 = offset.magnitude_squared();
+    let r = capsule.radius + sphere.radius;
+    if d2 >= r * r {
+        return None;
+    }
+
+    let normal1 = if d2 > 0.0 {
+        UnitVector3::new_normalize(offset)
+    } else {
+        Vector3::x_axis()
+    };
+    let normal2 = pos12.inverse_transform_unit_vector(&(-normal1));
+    let point1 = closest_point + capsule.radius * normal1.into_inner();
+    let point2 = (sphere.radius * normal2.into_inner()).into();
+    let separation_distance = d2.sqrt() - r;
+
+    Some(Contact::new(
+        point1,
+        point2,
+        normal1,
+        normal2,
+        separation_distance,
+        0.0,
+    ))
+}
@@ -0,0 +1,149 @@
+use crate::math::*;
+use crate::physics::*;
+
+/// Returns the deepest contact between a child of the compound and the shape.
+/// child1 of the contact is the index of the child.
+/// pos12 is shape's position relative to compound.
+pub fn contact_compound_shape(
+    compound: &Compound,
+    shape: &ShapeType,
+    pos12: &Isometry3,
+) -> Option<Contact> {
+    let aabb = shape.build_aabb(pos12);
+
+    let mut deepest: Option<Contact> = None;
+    for (i, child) in compound.children().iter().enumerate() {
+        if !child.shape.build_aabb(&child.position).intersect(&aabb) {
+            continue;
+        }
+        let child_pos12 = child.position.inverse() * pos12;
+        if let Some(mut contact) = child.shape.contact(shape, &child_pos12) {
+            // convert from the child's coordinates to the compound's.
+            contact.point1 = child.position.transform_point(&contact.point1);
+            contact.normal1 = child.position.rotation * contact.normal1;
+            contact.child1 = Some(i);
+            deepest = match deepest {
+                Some(d) if d.separation_distance <= contact.separation_distance => Some(d),
+                _ => Some(contact),
+            };
+        }
+    }
+    deepest
+}
+
+/// Stores the contact manifolds of every child of the compound touching the shape,
+/// so that a compound resting on several children gets points under each of them.
+/// child1 of a contact is the index of the child, which is also mixed into its feature.
+/// pos12 is shape's position relative to compound.
+pub fn contact_manifold_compound_shape<S: Storage<Contact>>(
+    compound: &Compound,
+    shape: &ShapeType,
+    pos12: &Isometry3,
+    manifold: &mut S,
+) {
+    let mut aabb = shape.build_aabb(pos12);
+    aabb.radii.add_scalar_mut(CONTACT_MARGIN);
+
+    let mut child_manifold = Vec::new();
+    for (i, child) in compound.children().iter().enumerate() {
+        if !child.shape.build_aabb(&child.position).intersect(&aabb) {
+            continue;
+        }
+        let child_pos12 = child.position.inverse() * pos12;
+        child_manifold.clear();
+        child
+            .shape
+            .contact_manifold(shape, &child_pos12, &mut child_manifold);
+        for mut contact in child_manifold.drain(..) {
+            // convert from the child's coordinates to the compound's.
+            contact.point1 = child.position.transform_point(&contact.point1);
+            contact.normal1 = child.position.rotation * contact.normal1;
+            contact.child1 = Some(i);
+            contact.feature = contact.feature.rotate_left(8) ^ i as u32;
+            manifold.store(contact);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_contact_compound_shape() {
+        // an L shaped piece.
+        let compound = Compound::new(vec![
+            CompoundChild::new(
+                Isometry3::translation(0.0, 0.0, 0.0),
+                ShapeType::Cube(Cube::new(Vector3::new(2.0, 0.5, 0.5))),
+                4.0,
+            ),
+            CompoundChild::new(
+                Isometry3::translation(1.5, 1.5, 0.0),
+                ShapeType::Cube(Cube::new(Vector3::new(0.5, 1.0, 0.5))),
+                2.0,
+            ),
+        ]);
+        let sphere = ShapeType::Sphere(Sphere::new(0.5));
+
+        let pos12 = Isometry3::translation(-1.0, 0.9, 0.0);
+        let contact = contact_compound_shape(&compound, &sphere, &pos12).unwrap();
+        assert_eq!(contact.child1, Some(0));
+        assert!((contact.point1 - Point3::new(-1.0, 0.5, 0.0)).magnitude() < 1.0e-5);
+
+        let pos12 = Isometry3::translation(0.6, 2.0, 0.0);
+        let contact = contact_compound_shape(&compound, &sphere, &pos12).unwrap();
+        assert_eq!(contact.child1, Some(1));
+        assert!((contact.point1 - Point3::new(1.0, 2.0, 0.0)).magnitude() < 1.0e-5);
+        assert!((contact.normal1.into_inner() + Vector3::x()).magnitude() < 1.0e-5);
+
+        let pos12 = Isometry3::translation(-1.0, 2.0, 0.0);
+        assert_eq!(contact_compound_shape(&compound, &sphere, &pos12), None);
+
+        let contact = ShapeType::Compound(compound)
+            .contact(&sphere, &Isometry3::translation(0.6, 2.0, 0.0))
+            .unwrap();
+        assert_eq!(contact.child1, Some(1));
+        let contact = sphere
+            .contact(
+                &ShapeType::Compound(Compound::new(vec![CompoundChild::new(
+                    Isometry3::translation(0.8, 0.0, 0.0),
+                    ShapeType::Sphere(Sphere::new(0.5)),
+                    1.0,
+                )])),
+                &Isometry3::translation(0.0, 0.0, 0.0),
+            )
+            .unwrap();
+        assert_eq!(contact.child2, Some(0));
+        assert_eq!(contact.child1, None);
+    }
+
+    #[test]
+    fn test_contact_manifold_compound_shape() {
+        // a bench standing on two legs.
+        let leg = || ShapeType::Cube(Cube::new(Vector3::new(0.1, 0.5, 0.1)));
+        let bench = ShapeType::Compound(Compound::new(vec![
+            CompoundChild::new(
+                Isometry3::translation(0.0, 0.6, 0.0),
+                ShapeType::Cube(Cube::new(Vector3::new(1.0, 0.1, 0.3))),
+                2.0,
+            ),
+            CompoundChild::new(Isometry3::translation(-0.9, 0.0, 0.0), leg(), 1.0),
+            CompoundChild::new(Isometry3::translation(0.9, 0.0, 0.0), leg(), 1.0),
+        ]));
+        let plane = ShapeType::Plane(Plane::new(Vector3::y_axis(), 0.0));
+
+        let pos12 = Isometry3::translation(0.0, 0.49, 0.0);
+        let mut manifold = Vec::new();
+        plane.contact_manifold(&bench, &pos12, &mut manifold);
+        assert!(manifold.len() >= 2);
+        for child in [1, 2] {
+            assert!(manifold.iter().any(|c| c.child2 == Some(child)));
+        }
+        assert!(manifold.iter().all(|c| c.child2 != Some(0)));
+        assert!(manifold.iter().all(|c| c.point1.y.abs() < 1.0e-5));
+        let mut features = manifold.iter().map(|c| c.feature).collect::<Vec<_>>();
+        features.sort_unstable();
+        features.dedup();
+        assert_eq!(features.len(), manifold.len());
+    }
+}
@@ -0,0 +1,56 @@
+use crate::math::*;
+use crate::physics::*;
+
+/// Returns contact information of any two convex shapes using GJK and EPA.
+/// pos12 is shape2's position relative to shape1.
+pub fn contact_convex_convex(
+    shape1: &dyn Shape,
+    shape2: &dyn Shape,
+    pos12: &Isometry3,
+) -> Option<Contact> {
+    let simplex = match gjk(shape1, shape2, pos12) {
+        GjkResult::Intersection(simplex) => simplex,
+        GjkResult::Separation { .. } => return None,
+    };
+    let penetration = epa(shape1, shape2, pos12, &simplex)?;
+
+    let normal1 = penetration.normal;
+    let normal2 = pos12.inverse_transform_unit_vector(&(-normal1));
+    Some(Contact::new(
+        penetration.point1,
+        penetration.point2,
+        normal1,
+        normal2,
+        -penetration.depth,
+        0.0,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_contact_convex_convex() {
+        let s1 = Sphere::new(1.0);
+        let s2 = Sphere::new(100.0);
+        let pos12 = Isometry3::translation(0.0, -100.5, 0.0);
+        let expected = contact_sphere_sphere(&s1, &s2, &pos12).unwrap();
+        let contact = contact_convex_convex(&s1, &s2, &pos12).unwrap();
+        assert!((contact.separation_distance - expected.separation_distance).abs() < 1.0e-2);
+        assert!(
+            (contact.normal1.into_inner() - expected.normal1.into_inner()).magnitude() < 1.0e-2
+        );
+
+        let c = Cube::new(Vector3::new(1.0, 1.0, 1.0));
+        let s = Sphere::new(1.0);
+        let pos12 = Isometry3::translation(1.5, 0.0, 0.0);
+        let expected = contact_cube_sphere(&c, &s, &pos12).unwrap();
+        let contact = contact_convex_convex(&c, &s, &pos12).unwrap();
+        assert!((contact.separation_distance - expected.separation_distance).abs() < 1.0e-2);
+        assert!((contact.point1 - expected.point1).magnitude() < 1.0e-2);
+        assert!((contact.point2 - expected.point2).magnitude() < 1.0e-2);
+
+        let pos12 = Isometry3::translation(3.0, 0.0, 0.0);
+        assert_eq!(contact_convex_convex(&c, &s, &pos12), None);
+    }
+}
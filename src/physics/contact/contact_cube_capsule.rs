@@ -0,0 +1,171 @@
+use crate::math::*;
+use crate::physics::*;
+
+fn clamp_to_cube(cube: &Cube, p: &Point3) -> Point3 {
+    Point3::new(
+        p.x.clamp(-cube.half_extents.x, cube.half_extents.x),
+        p.y.clamp(-cube.half_extents.y, cube.half_extents.y),
+        p.z.clamp(-cube.half_extents.z, cube.half_extents.z),
+    )
+}
+
+/// Returns the closest points on the segment ab and the cube.
+/// The squared distance is piecewise quadratic along the segment,
+/// so it is minimized on each interval between the points where the segment crosses the cube's slabs.
+fn closest_points_segment_cube(cube: &Cube, a: &Point3, b: &Point3) -> (Point3, Point3) {
+    let h = cube.half_extents;
+    let d = b - a;
+    let mut breaks = vec![0.0, 1.0];
+    for i in 0..3 {
+        if d[i] != 0.0 {
+            for bound in [-h[i], h[i]] {
+                let t = (bound - a[i]) / d[i];
+                if t > 0.0 && t < 1.0 {
+                    breaks.push(t);
+                }
+            }
+        }
+    }
+    breaks.sort_by(|t1, t2| t1.partial_cmp(t2).unwrap());
+
+    let mut best_t = 0.0;
+    let mut best_d2 = FLOAT_MAX;
+    for interval in breaks.windows(2) {
+        let (t0, t1) = (interval[0], interval[1]);
+        let mid = a + d * ((t0 + t1) * 0.5);
+        let mut num = 0.0;
+        let mut den = 0.0;
+        for i in 0..3 {
+            let c = if mid[i] > h[i] {
+                a[i] - h[i]
+            } else if mid[i] < -h[i] {
+                a[i] + h[i]
+            } else {
+                continue;
+            };
+            num -= c * d[i];
+            den += d[i] * d[i];
+        }
+        let t = if den > 0.0 {
+            (num / den).clamp(t0, t1)
+        } else {
+            t0
+        };
+        let p = a + d * t;
+        let d2 = (p - clamp_to_cube(cube, &p)).magnitude_squared();
+        if d2 < best_d2 {
+            best_d2 = d2;
+            best_t = t;
+        }
+    }
+    let p = a + d * best_t;
+    (p, clamp_to_cube(cube, &p))
+}
+
+/// Returns contact information if they contact.
+/// pos12 is capsule's position relative to cube.
+pub fn contact_cube_capsule(cube: &Cube, capsule: &Capsule, pos12: &Isometry3) -> Option<Contact> {
+    let (a, b) = capsule.segment_world(pos12);
+    let (point_on_segment, point_on_cube) = closest_points_segment_cube(cube, &a, &b);
+    let offset = point_on_segment - point_on_cube;
+    let distance_squared = offset.magnitude_squared();
+    if distance_squared >= capsule.radius * capsule.radius {
+        return None;
+    }
+
+    let (point1, point_on_capsule, normal1, separation_distance) = if distance_squared > 0.0 {
+        let distance = distance_squared.sqrt();
+        let normal1 = UnitVector3::new_normalize(offset);
+        (
+            point_on_cube,
+            point_on_segment - normal1.into_inner() * capsule.radius,
+            normal1,
+            distance - capsule.radius,
+        )
+    } else {
+        // the segment is inside the cube. push out along the face of least penetration.
+        let mut best_penetration = FLOAT_MAX;
+        let mut best = (Vector3::zeros(), Point3::origin());
+        for i in 0..3 {
+            for sign in [-1.0, 1.0] {
+                let mut normal = Vector3::zeros();
+                normal[i] = sign;
+                let (da, db) = (a.coords.dot(&normal), b.coords.dot(&normal));
+                let deepest = if (da - db).abs() < 1.0e-4 {
+                    Point3::from((a.coords + b.coords) * 0.5)
+                } else if da < db {
+                    a
+                } else {
+                    b
+                };
+                let penetration =
+                    cube.half_extents[i] - deepest.coords.dot(&normal) + capsule.radius;
+                if penetration < best_penetration {
+                    best_penetration = penetration;
+                    best = (normal, deepest);
+                }
+            }
+        }
+        let (normal, deepest) = best;
+        let point_on_capsule = deepest - normal * capsule.radius;
+        (
+            point_on_capsule + normal * best_penetration,
+            point_on_capsule,
+            UnitVector3::new_unchecked(normal),
+            -best_penetration,
+        )
+    };
+
+    let normal2 = pos12.inverse_transform_unit_vector(&(-normal1));
+    let point2 = pos12.inverse_transform_point(&point_on_capsule);
+    Some(Contact::new(
+        point1,
+        point2,
+        normal1,
+        normal2,
+        separation_distance,
+        0.0,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_contact_cube_capsule() {
+        let cube = Cube::new(Vector3::new(1.0, 1.0, 1.0));
+        let capsule = Capsule::new(0.5, 1.0);
+
+        // lying on the top face.
+        let pos12 = Isometry3::new(
+            Vector3::new(0.0, 1.4, 0.0),
+            Vector3::new(0.0, 0.0, PI / 2.0),
+        );
+        let contact = contact_cube_capsule(&cube, &capsule, &pos12).unwrap();
+        assert!((contact.separation_distance + 0.1).abs() < 1.0e-4);
+        assert!((contact.normal1.into_inner() - Vector3::y()).magnitude() < 1.0e-4);
+        assert!((contact.point1.y - 1.0).abs() < 1.0e-4);
+
+        // lying along an edge.
+        let pos12 = Isometry3::new(
+            Vector3::new(1.3, 1.3, 0.0),
+            Vector3::new(PI / 2.0, 0.0, 0.0),
+        );
+        let contact = contact_cube_capsule(&cube, &capsule, &pos12).unwrap();
+        assert!((contact.point1.x - 1.0).abs() < 1.0e-4);
+        assert!((contact.point1.y - 1.0).abs() < 1.0e-4);
+        let expected = (0.3 as Float * 0.3 * 2.0).sqrt() - 0.5;
+        assert!((contact.separation_distance - expected).abs() < 1.0e-4);
+
+        // apart.
+        let pos12 = Isometry3::translation(0.0, 2.6, 0.0);
+        assert_eq!(contact_cube_capsule(&cube, &capsule, &pos12), None);
+
+        // segment inside the cube.
+        let capsule = Capsule::new(0.5, 0.2);
+        let pos12 = Isometry3::translation(0.0, 0.6, 0.0);
+        let contact = contact_cube_capsule(&cube, &capsule, &pos12).unwrap();
+        assert!((contact.normal1.into_inner() - Vector3::y()).magnitude() < 1.0e-4);
+        assert!((contact.separation_distance + 1.1).abs() < 1.0e-4);
+    }
+}
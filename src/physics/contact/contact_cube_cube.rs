@@ -1,6 +1,10 @@
 use crate::math::*;
 use crate::physics::*;
 
+/// Incident vertices this far outside the reference face are kept rather than clipped,
+/// so that boxes of the same size stacked on each other keep the same contact features.
+const CLIP_TOLERANCE: Float = 0.005;
+
 /// Return the overlap along the axis.
 /// Negative value indicates separation.
 fn penetration_on_axis(cube1: &Cube, cube2: &Cube, pos12: &Isometry3, axis: &Vector3) -> Float {
@@ -42,7 +46,15 @@ fn get_contact_point(
     (pt_on_edge1 + axis1 * a, pt_on_edge2 + axis2 * b)
 }
 
-pub fn contact_cube_cube(cube1: &Cube, cube2: &Cube, pos12: &Isometry3) -> Option<Contact> {
+/// Finds the axis of least penetration with the separating axis test.
+/// Returns the index of the axis, the axis pointing from cube1 to cube2 and the overlap,
+/// or None if the cubes are separated by more than margin.
+fn least_penetration_axis(
+    cube1: &Cube,
+    cube2: &Cube,
+    pos12: &Isometry3,
+    margin: Float,
+) -> Option<(usize, Vector3, Float)> {
     let axes1 = Matrix3::identity();
     let axes2 = pos12.rotation.to_rotation_matrix().into_inner();
     let mut axes: [Vector3; 15] = [
@@ -68,19 +80,24 @@ pub fn contact_cube_cube(cube1: &Cube, cube2: &Cube, pos12: &Isometry3) -> Optio
     let mut best_overlap = FLOAT_MAX;
     let mut best_index = 0;
 
-    for i in 0..15 {
-        let axis = &mut axes[i];
-
+    for (i, axis) in axes.iter_mut().enumerate() {
         if axis.magnitude_squared() < 0.001 {
             continue;
         }
         axis.normalize_mut();
 
         let overlap = penetration_on_axis(cube1, cube2, pos12, axis);
-        if overlap < 0.0 {
+        if overlap < -margin {
             return None;
         }
-        if overlap < best_overlap {
+        // later axes have to be clearly better than earlier ones, otherwise nearly resting
+        // boxes flicker between the faces of either cube and edge contacts.
+        let better = if i < 3 {
+            overlap < best_overlap
+        } else {
+            overlap < 0.98 * best_overlap - 0.001
+        };
+        if better {
             best_overlap = overlap;
             best_index = i;
         }
@@ -90,6 +107,13 @@ pub fn contact_cube_cube(cube1: &Cube, cube2: &Cube, pos12: &Isometry3) -> Optio
     if axis.dot(&pos12.translation.vector) < 0.0 {
         axis = -axis;
     }
+    Some((best_index, axis, best_overlap))
+}
+
+pub fn contact_cube_cube(cube1: &Cube, cube2: &Cube, pos12: &Isometry3) -> Option<Contact> {
+    let (best_index, axis, best_overlap) = least_penetration_axis(cube1, cube2, pos12, 0.0)?;
+    let axes1 = Matrix3::identity();
+    let axes2 = pos12.rotation.to_rotation_matrix().into_inner();
 
     // vertex-face contact
     let contact = if best_index < 3 {
@@ -118,6 +142,9 @@ pub fn contact_cube_cube(cube1: &Cube, cube2: &Cube, pos12: &Isometry3) -> Optio
             normal2,
             separation_distance,
             toi,
+            child1: None,
+            child2: None,
+            feature: 0,
         };
         Some(contact)
     } else if best_index < 6 {
@@ -145,6 +172,9 @@ pub fn contact_cube_cube(cube1: &Cube, cube2: &Cube, pos12: &Isometry3) -> Optio
             normal2,
             separation_distance,
             toi,
+            child1: None,
+            child2: None,
+            feature: 0,
         };
 
         Some(contact)
@@ -184,9 +214,234 @@ pub fn contact_cube_cube(cube1: &Cube, cube2: &Cube, pos12: &Isometry3) -> Optio
             normal2,
             separation_distance,
             toi,
+            child1: None,
+            child2: None,
+            feature: 0,
         };
 
         Some(contact)
     };
     contact
 }
+
+/// Computes the contact manifold of two cubes and stores up to four points.
+/// Face contacts are found by clipping the incident face against the reference face,
+/// edge-edge contacts give a single point.
+/// Points closer than `CONTACT_MARGIN` are kept with positive separation.
+pub fn contact_manifold_cube_cube<S: Storage<Contact>>(
+    cube1: &Cube,
+    cube2: &Cube,
+    pos12: &Isometry3,
+    manifold: &mut S,
+) {
+    let Some((best_index, axis, _)) = least_penetration_axis(cube1, cube2, pos12, CONTACT_MARGIN)
+    else {
+        return;
+    };
+    if best_index < 3 {
+        clip_incident_face(cube1, cube2, pos12, &axis, 0, manifold);
+    } else if best_index < 6 {
+        let pos21 = pos12.inverse();
+        let axis = pos12.inverse_transform_vector(&(-axis));
+        clip_incident_face(cube2, cube1, &pos21, &axis, 1 << 16, &mut Flipped(manifold));
+    } else if let Some(mut contact) = contact_cube_cube(cube1, cube2, pos12) {
+        contact.feature = 2 << 16 | best_index as u32;
+        manifold.store(contact);
+    }
+}
+
+/// Clips the incident cube's face against the reference cube's face whose normal is `normal`.
+/// All computation is in the reference cube's coordinates,
+/// and pos_ri is the incident cube's position relative to the reference cube.
+/// Feature ids of the contacts are made of the faces, the incident vertex or the clipping plane,
+/// added to feature_base.
+fn clip_incident_face<S: Storage<Contact>>(
+    reference: &Cube,
+    incident: &Cube,
+    pos_ri: &Isometry3,
+    normal: &Vector3,
+    feature_base: u32,
+    manifold: &mut S,
+) {
+    let reference_axis = normal.iamax();
+    let reference_offset = reference.half_extents[reference_axis];
+
+    // the incident face is the one most anti parallel to the normal.
+    let dir = pos_ri.inverse_transform_vector(&(-normal));
+    let incident_axis = dir.iamax();
+    let (a, b) = ((incident_axis + 1) % 3, (incident_axis + 2) % 3);
+    let mut face = Vec::with_capacity(8);
+    for (vertex, (sa, sb)) in [(1.0, 1.0), (-1.0, 1.0), (-1.0, -1.0), (1.0, -1.0)]
+        .into_iter()
+        .enumerate()
+    {
+        let mut p = Point3::origin();
+        p[incident_axis] = incident.half_extents[incident_axis] * dir[incident_axis].signum();
+        p[a] = incident.half_extents[a] * sa;
+        p[b] = incident.half_extents[b] * sb;
+        face.push((pos_ri.transform_point(&p), vertex as u32));
+    }
+
+    // clip against the side planes of the reference face.
+    let mut plane = 0;
+    for side in (0..3).filter(|i| *i != reference_axis) {
+        for sign in [1.0, -1.0] {
+            let offset = reference.half_extents[side] + CLIP_TOLERANCE;
+            face = clip_polygon(&face, side, sign, offset, plane);
+            plane += 1;
+        }
+    }
+
+    let face_feature = |axis: usize, dir: Float| (axis * 2 + (dir < 0.0) as usize) as u32;
+    let feature_base = feature_base
+        | face_feature(reference_axis, normal[reference_axis]) << 8
+        | face_feature(incident_axis, dir[incident_axis]) << 12;
+    let mut points: Vec<(Point3, Float, u32)> = face
+        .into_iter()
+        .map(|(p, feature)| (p, normal.dot(&p.coords) - reference_offset, feature))
+        .filter(|(_, depth, _)| *depth <= CONTACT_MARGIN)
+        .collect();
+    reduce_points(&mut points, normal);
+
+    let normal1 = UnitVector3::new_unchecked(*normal);
+    let normal2 = pos_ri.inverse_transform_unit_vector(&(-normal1));
+    for (p, separation_distance, feature) in points {
+        let point1 = p - normal * separation_distance;
+        let point2 = pos_ri.inverse_transform_point(&p);
+        let mut contact = Contact::new(point1, point2, normal1, normal2, separation_distance, 0.0);
+        contact.feature = feature_base | feature;
+        manifold.store(contact);
+    }
+}
+
+/// Sutherland-Hodgman clipping of a polygon against the plane sign * p[axis] <= offset.
+/// Points are tagged with a feature id. Points made by the clipping get the id of the plane
+/// and of the edge's first point.
+fn clip_polygon(
+    polygon: &[(Point3, u32)],
+    axis: usize,
+    sign: Float,
+    offset: Float,
+    plane: u32,
+) -> Vec<(Point3, u32)> {
+    let mut clipped = Vec::with_capacity(polygon.len() + 1);
+    for i in 0..polygon.len() {
+        let (p1, f1) = polygon[i];
+        let (p2, _) = polygon[(i + 1) % polygon.len()];
+        let d1 = sign * p1[axis] - offset;
+        let d2 = sign * p2[axis] - offset;
+        if d1 <= 0.0 {
+            clipped.push((p1, f1));
+        }
+        if (d1 < 0.0 && d2 > 0.0) || (d1 > 0.0 && d2 < 0.0) {
+            let t = d1 / (d1 - d2);
+            clipped.push((p1 + (p2 - p1) * t, 4 + plane * 4 + f1 % 4));
+        }
+    }
+    clipped
+}
+
+/// Keeps at most four points, the deepest one and the ones spanning the largest area.
+fn reduce_points(points: &mut Vec<(Point3, Float, u32)>, normal: &Vector3) {
+    if points.len() <= 4 {
+        return;
+    }
+    let deepest = (0..points.len())
+        .min_by(|i, j| points[*i].1.total_cmp(&points[*j].1))
+        .unwrap();
+    let p0 = points[deepest].0;
+    let farthest = (0..points.len())
+        .max_by(|i, j| {
+            let di = (points[*i].0 - p0).magnitude_squared();
+            let dj = (points[*j].0 - p0).magnitude_squared();
+            di.total_cmp(&dj)
+        })
+        .unwrap();
+    let p1 = points[farthest].0;
+    let area = |p: &Point3| (p1 - p0).cross(&(p - p0)).dot(normal);
+    let (left, right) = (0..points.len()).fold((deepest, deepest), |(left, right), i| {
+        let a = area(&points[i].0);
+        (
+            if a > area(&points[left].0) { i } else { left },
+            if a < area(&points[right].0) { i } else { right },
+        )
+    });
+
+    let mut kept = vec![deepest, farthest, left, right];
+    kept.sort_unstable();
+    kept.dedup();
+    *points = kept.into_iter().map(|i| points[i]).collect();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_contact_manifold_cube_cube() {
+        // a box resting on a wider box gets a point at each bottom corner.
+        let cube1 = Cube::new(Vector3::new(2.0, 1.0, 2.0));
+        let cube2 = Cube::new(Vector3::new(0.5, 0.5, 0.5));
+        let pos12 = Isometry3::new(
+            Vector3::new(0.3, 1.4, -0.2),
+            Vector3::new(0.0, PI / 6.0, 0.0),
+        );
+        let mut manifold = Vec::new();
+        contact_manifold_cube_cube(&cube1, &cube2, &pos12, &mut manifold);
+        assert_eq!(manifold.len(), 4);
+        for contact in &manifold {
+            assert!((contact.separation_distance + 0.1).abs() < 1.0e-4);
+            assert!((contact.point1.y - 1.0).abs() < 1.0e-4);
+            assert!((contact.point2.y + 0.5).abs() < 1.0e-4);
+            assert!((contact.normal1.into_inner() - Vector3::y()).magnitude() < 1.0e-4);
+        }
+
+        // the reference face is on cube2 when it's the wider one.
+        let pos21 = pos12.inverse();
+        let mut flipped = Vec::new();
+        contact_manifold_cube_cube(&cube2, &cube1, &pos21, &mut flipped);
+        assert_eq!(flipped.len(), 4);
+        for contact in &flipped {
+            assert!((contact.separation_distance + 0.1).abs() < 1.0e-4);
+            assert!((contact.point1.y + 0.5).abs() < 1.0e-4);
+            assert!((contact.point2.y - 1.0).abs() < 1.0e-4);
+        }
+
+        // an overhanging box is clipped to the reference face.
+        let pos12 = Isometry3::translation(2.0, 1.4, 0.0);
+        let mut manifold = Vec::new();
+        contact_manifold_cube_cube(&cube1, &cube2, &pos12, &mut manifold);
+        assert_eq!(manifold.len(), 4);
+        let edge = 2.0 + CLIP_TOLERANCE;
+        assert!(manifold.iter().all(|c| c.point1.x <= edge + 1.0e-4));
+        assert!(manifold.iter().any(|c| (c.point1.x - edge).abs() < 1.0e-4));
+
+        let pos12 = Isometry3::translation(0.0, 1.6, 0.0);
+        let mut manifold = Vec::new();
+        contact_manifold_cube_cube(&cube1, &cube2, &pos12, &mut manifold);
+        assert!(manifold.is_empty());
+    }
+
+    #[test]
+    fn test_contact_manifold_features() {
+        // boxes of the same size keep their features when they shift slightly.
+        let cube = Cube::new(Vector3::new(0.5, 0.5, 0.5));
+        let features = |pos12: &Isometry3| {
+            let mut manifold = Vec::new();
+            contact_manifold_cube_cube(&cube, &cube, pos12, &mut manifold);
+            let mut features = manifold.iter().map(|c| c.feature).collect::<Vec<_>>();
+            features.sort_unstable();
+            features
+        };
+        let resting = features(&Isometry3::translation(0.0, 0.99, 0.0));
+        let shifted = features(&Isometry3::new(
+            Vector3::new(0.002, 0.99, -0.001),
+            Vector3::new(0.0, 0.002, 0.0),
+        ));
+        assert_eq!(resting.len(), 4);
+        assert_eq!(resting, shifted);
+        let mut unique = resting.clone();
+        unique.dedup();
+        assert_eq!(unique, resting);
+    }
+}
@@ -47,5 +47,8 @@ pub fn contact_cube_sphere(cube: &Cube, sphere: &Sphere, pos12: &Isometry3) -> O
         normal2,
         separation_distance,
         toi,
+        child1: None,
+        child2: None,
+        feature: 0,
     })
 }
@@ -0,0 +1,55 @@
+use crate::math::*;
+use crate::physics::*;
+
+/// Returns the deepest contact between the height field and a convex shape.
+/// Only cells under the shape's bounding box are tested.
+/// pos12 is shape's position relative to height field.
+pub fn contact_height_field_shape(
+    field: &HeightField,
+    shape: &ShapeType,
+    pos12: &Isometry3,
+) -> Option<Contact> {
+    let aabb = shape.build_aabb(pos12);
+    let (rows, cols) = field.cells_in(&aabb)?;
+
+    let mut deepest: Option<Contact> = None;
+    for row in rows {
+        for col in cols.clone() {
+            for triangle in &field.cell_triangles(row, col) {
+                if let Some(contact) = contact_triangle_shape(triangle, shape, pos12) {
+                    deepest = match deepest {
+                        Some(d) if d.separation_distance <= contact.separation_distance => Some(d),
+                        _ => Some(contact),
+                    };
+                }
+            }
+        }
+    }
+    deepest
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_contact_height_field_shape() {
+        // a slope rising along x by 1 per unit.
+        let heights = (0..5)
+            .flat_map(|_| (0..5).map(|col| col as Float))
+            .collect::<Vec<_>>();
+        let field = HeightField::new(heights, 5, 5, Vector3::new(4.0, 1.0, 4.0));
+
+        let sphere = ShapeType::Sphere(Sphere::new(1.0));
+        let pos12 = Isometry3::translation(0.0, 3.0, 0.0);
+        let contact = contact_height_field_shape(&field, &sphere, &pos12).unwrap();
+        let expected_normal = Vector3::new(-1.0, 1.0, 0.0).normalize();
+        assert!((contact.normal1.into_inner() - expected_normal).magnitude() < 1.0e-4);
+        assert!((contact.separation_distance - (Float::sqrt(0.5) - 1.0)).abs() < 1.0e-4);
+
+        let cube = ShapeType::Cube(Cube::new(Vector3::new(0.5, 0.5, 0.5)));
+        let pos12 = Isometry3::translation(0.0, 3.6, 0.0);
+        assert_eq!(contact_height_field_shape(&field, &cube, &pos12), None);
+        let pos12 = Isometry3::translation(0.0, 2.8, 0.0);
+        assert!(contact_height_field_shape(&field, &cube, &pos12).is_some());
+    }
+}
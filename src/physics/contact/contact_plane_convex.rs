@@ -0,0 +1,79 @@
+use crate::math::*;
+use crate::physics::*;
+
+/// Returns contact information of a plane and a convex shape.
+/// The deepest point of the shape is its supporting point against the plane's normal.
+/// pos12 is shape's position relative to plane.
+pub fn contact_plane_convex(
+    plane: &Plane,
+    shape: &dyn Shape,
+    pos12: &Isometry3,
+) -> Option<Contact> {
+    let normal1 = plane.normal;
+    let normal2 = pos12.inverse_transform_unit_vector(&(-normal1));
+    let point2 = shape.supporting_point(&normal2, 0.0);
+    let deepest = pos12.transform_point(&point2);
+    let separation_distance = plane.distance(&deepest);
+    if separation_distance >= 0.0 {
+        return None;
+    }
+    let point1 = plane.project_point(&deepest);
+    Some(Contact::new(
+        point1,
+        point2,
+        normal1,
+        normal2,
+        separation_distance,
+        0.0,
+    ))
+}
+
+/// Stores a contact for every corner of the cube closer to the plane than `CONTACT_MARGIN`,
+/// so that a cube resting on the plane gets a point at each corner of its bottom face.
+/// The feature id of a contact is the index of the corner.
+/// pos12 is cube's position relative to plane.
+pub fn contact_manifold_plane_cube<S: Storage<Contact>>(
+    plane: &Plane,
+    cube: &Cube,
+    pos12: &Isometry3,
+    manifold: &mut S,
+) {
+    let normal1 = plane.normal;
+    let normal2 = pos12.inverse_transform_unit_vector(&(-normal1));
+    for (corner_index, point2) in cube.corners().into_iter().enumerate() {
+        let corner = pos12.transform_point(&point2);
+        let separation_distance = plane.distance(&corner);
+        if separation_distance < CONTACT_MARGIN {
+            let point1 = plane.project_point(&corner);
+            let mut contact =
+                Contact::new(point1, point2, normal1, normal2, separation_distance, 0.0);
+            contact.feature = corner_index as u32;
+            manifold.store(contact);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_contact_plane_convex() {
+        let plane = Plane::new(Vector3::y_axis(), 1.0);
+        let cube = Cube::new(Vector3::new(1.0, 1.0, 1.0));
+        let pos12 = Isometry3::new(
+            Vector3::new(5.0, 2.0, -3.0),
+            Vector3::new(0.0, 0.0, PI / 4.0),
+        );
+        let contact = contact_plane_convex(&plane, &cube, &pos12).unwrap();
+        let expected = 1.0 - (2.0 - Float::sqrt(2.0));
+        assert!((contact.separation_distance + expected).abs() < 1.0e-5);
+        // the lowest feature is an edge along z, so only x and y are fixed.
+        assert!((contact.point1.x - 5.0).abs() < 1.0e-4);
+        assert!((contact.point1.y - 1.0).abs() < 1.0e-4);
+        assert_eq!(contact.normal1, Vector3::y_axis());
+
+        let sphere = Sphere::new(1.0);
+        let pos12 = Isometry3::translation(100.0, 2.5, 40.0);
+        assert_eq!(contact_plane_convex(&plane, &sphere, &pos12), None);
+    }
+}
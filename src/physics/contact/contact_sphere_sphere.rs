@@ -47,6 +47,9 @@ mod test {
             normal2: UnitVector3::new_normalize(Vector3::new(0.0, 1.0, 0.0)),
             separation_distance: -0.5,
             toi: 0.0,
+            child1: None,
+            child2: None,
+            feature: 0,
         };
 
         assert_eq!(contact.unwrap(), expected);
@@ -0,0 +1,78 @@
+use crate::math::*;
+use crate::physics::*;
+
+/// Returns contact information of a triangle and a convex shape.
+/// pos12 is shape's position relative to triangle.
+pub fn contact_triangle_shape(
+    triangle: &Triangle,
+    shape: &ShapeType,
+    pos12: &Isometry3,
+) -> Option<Contact> {
+    match shape {
+        ShapeType::Sphere(s) => contact_triangle_sphere(triangle, s, pos12),
+        _ => contact_convex_convex(triangle, shape.as_shape(), pos12),
+    }
+}
+
+/// Returns the deepest contact between the mesh and a convex shape.
+/// Only triangles overlapping with the shape's bounding box are tested.
+/// pos12 is shape's position relative to mesh.
+pub fn contact_tri_mesh_shape(
+    mesh: &TriMesh,
+    shape: &ShapeType,
+    pos12: &Isometry3,
+) -> Option<Contact> {
+    let aabb = shape.build_aabb(pos12);
+    let mut triangles = Vec::new();
+    mesh.query_triangles(&aabb, &mut triangles);
+
+    let mut deepest: Option<Contact> = None;
+    for i in triangles {
+        if let Some(contact) = contact_triangle_shape(&mesh.triangle(*i), shape, pos12) {
+            deepest = match deepest {
+                Some(d) if d.separation_distance <= contact.separation_distance => Some(d),
+                _ => Some(contact),
+            };
+        }
+    }
+    deepest
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_contact_tri_mesh_shape() {
+        // a 10x10 grid of quads on the xz plane.
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for i in 0..11 {
+            for j in 0..11 {
+                vertices.push(Point3::new(i as Float - 5.0, 0.0, j as Float - 5.0));
+            }
+        }
+        for i in 0..10 {
+            for j in 0..10 {
+                let a = i * 11 + j;
+                let (b, c, d) = (a + 1, a + 12, a + 11);
+                indices.extend_from_slice(&[a, b, c, a, c, d]);
+            }
+        }
+        let mesh = TriMesh::new(vertices, &indices);
+
+        let sphere = ShapeType::Sphere(Sphere::new(1.0));
+        let pos12 = Isometry3::translation(0.3, 0.8, 2.6);
+        let contact = contact_tri_mesh_shape(&mesh, &sphere, &pos12).unwrap();
+        assert!((contact.separation_distance + 0.2).abs() < 1.0e-4);
+        assert!((contact.normal1.into_inner() - Vector3::y()).magnitude() < 1.0e-4);
+        assert!((contact.point1 - Point3::new(0.3, 0.0, 2.6)).magnitude() < 1.0e-4);
+
+        let cube = ShapeType::Cube(Cube::new(Vector3::new(0.5, 0.5, 0.5)));
+        let pos12 = Isometry3::translation(-1.2, 0.4, 3.3);
+        let contact = contact_tri_mesh_shape(&mesh, &cube, &pos12).unwrap();
+        assert!((contact.separation_distance + 0.1).abs() < 1.0e-3);
+
+        let pos12 = Isometry3::translation(-1.2, 0.6, 3.3);
+        assert_eq!(contact_tri_mesh_shape(&mesh, &cube, &pos12), None);
+    }
+}
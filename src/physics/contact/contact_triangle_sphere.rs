@@ -0,0 +1,35 @@
+use crate::math::*;
+use crate::physics::*;
+
+/// Returns contact information if they contact.
+/// pos12 is sphere's position relative to triangle.
+pub fn contact_triangle_sphere(
+    triangle: &Triangle,
+    sphere: &Sphere,
+    pos12: &Isometry3,
+) -> Option<Contact> {
+    let center: Point3 = pos12.translation.vector.into();
+    let closest_point = triangle.closest_point(&center);
+    let offset = center - closest_point;
+    let d2 = offset.magnitude_squared();
+    if d2 >= sphere.radius * sphere.radius {
+        return None;
+    }
+
+    let normal1 = if d2 > 0.0 {
+        UnitVector3::new_normalize(offset)
+    } else {
+        triangle.normal()?
+    };
+    let normal2 = pos12.inverse_transform_unit_vector(&(-normal1));
+    let point2 = (sphere.radius * normal2.into_inner()).into();
+    let separation_distance = d2.sqrt() - sphere.radius;
+    Some(Contact::new(
+        closest_point,
+        point2,
+        normal1,
+        normal2,
+        separation_distance,
+        0.0,
+    ))
+}
@@ -0,0 +1,162 @@
+use super::*;
+use crate::math::*;
+
+/// Keeps the distance between two anchors within [min_length, max_length],
+/// pulling or pushing them back with a damped spring when it isn't.
+/// An anchor is a point on a body, or a fixed point in the world.
+/// Unlike joints, the constraint is soft and is applied to the bodies as impulses
+/// before the solver runs.
+#[derive(Debug, Clone)]
+pub struct DistanceConstraint {
+    body1: RigidBodyId,
+    /// In body1's coordinates.
+    anchor1: Point3,
+    body2: Option<RigidBodyId>,
+    /// In body2's coordinates, or in world coordinates without body2.
+    anchor2: Point3,
+    min_length: Float,
+    max_length: Float,
+    stiffness: Float,
+    damping: Float,
+}
+
+impl DistanceConstraint {
+    /// Creates a constraint between points on two bodies, in their own coordinates.
+    pub fn new(
+        (body1, anchor1): (RigidBodyId, Point3),
+        (body2, anchor2): (RigidBodyId, Point3),
+        min_length: Float,
+        max_length: Float,
+    ) -> Self {
+        Self {
+            body1,
+            anchor1,
+            body2: Some(body2),
+            anchor2,
+            min_length,
+            max_length,
+            stiffness: 1000.0,
+            damping: 50.0,
+        }
+    }
+
+    /// Creates a constraint between a point on a body, in its coordinates,
+    /// and a point in world coordinates.
+    pub fn new_world(
+        (body1, anchor1): (RigidBodyId, Point3),
+        point: Point3,
+        min_length: Float,
+        max_length: Float,
+    ) -> Self {
+        Self {
+            body2: None,
+            anchor2: point,
+            ..Self::new((body1, anchor1), (body1, point), min_length, max_length)
+        }
+    }
+
+    pub fn get_bodies(&self) -> (RigidBodyId, Option<RigidBodyId>) {
+        (self.body1, self.body2)
+    }
+    pub fn get_anchor1(&self) -> &Point3 {
+        &self.anchor1
+    }
+    pub fn set_anchor1(&mut self, anchor: &Point3) {
+        self.anchor1 = *anchor;
+    }
+    pub fn get_anchor2(&self) -> &Point3 {
+        &self.anchor2
+    }
+    pub fn set_anchor2(&mut self, anchor: &Point3) {
+        self.anchor2 = *anchor;
+    }
+    pub fn get_min_length(&self) -> Float {
+        self.min_length
+    }
+    pub fn set_min_length(&mut self, length: Float) {
+        self.min_length = length;
+    }
+    pub fn get_max_length(&self) -> Float {
+        self.max_length
+    }
+    pub fn set_max_length(&mut self, length: Float) {
+        self.max_length = length;
+    }
+    pub fn get_stiffness(&self) -> Float {
+        self.stiffness
+    }
+    pub fn set_stiffness(&mut self, stiffness: Float) {
+        self.stiffness = stiffness;
+    }
+    pub fn get_damping(&self) -> Float {
+        self.damping
+    }
+    pub fn set_damping(&mut self, damping: Float) {
+        self.damping = damping;
+    }
+
+    /// Returns the anchors in world coordinates.
+    pub fn world_anchors(&self, body1: &RigidBody, body2: Option<&RigidBody>) -> (Point3, Point3) {
+        let anchor2 = match body2 {
+            Some(body2) => body2.local_to_world_point(&self.anchor2),
+            None => self.anchor2,
+        };
+        (body1.local_to_world_point(&self.anchor1), anchor2)
+    }
+
+    /// Applies the spring impulse of a step to the bodies.
+    /// body2 is None if the constraint is attached to a point in the world.
+    pub fn apply(&self, body1: &mut RigidBody, body2: Option<&mut RigidBody>, delta_time: Float) {
+        let (point1, point2) = self.world_anchors(body1, body2.as_deref());
+        let delta = point2 - point1;
+        let length = delta.magnitude();
+        let error = if length < self.min_length {
+            length - self.min_length
+        } else if length > self.max_length {
+            length - self.max_length
+        } else {
+            return;
+        };
+        if length <= FLOAT_MIN_POSITIVE {
+            return;
+        }
+        let normal = delta / length;
+
+        let r1 = point1 - body1.get_center_of_mass_world();
+        let mut velocity = -(body1.get_linear_velocity() + body1.get_angular_velocity().cross(&r1));
+        let mut inv_mass = inv_effective_mass(body1, &r1, &normal);
+        if let Some(body2) = &body2 {
+            let r2 = point2 - body2.get_center_of_mass_world();
+            velocity += body2.get_linear_velocity() + body2.get_angular_velocity().cross(&r2);
+            inv_mass += inv_effective_mass(body2, &r2, &normal);
+        }
+
+        // implicit spring, stable for any stiffness and step.
+        let h = delta_time;
+        let softness = h * (self.damping + h * self.stiffness);
+        if softness <= 0.0 {
+            return;
+        }
+        let gamma = 1.0 / softness;
+        let beta = h * self.stiffness / (self.damping + h * self.stiffness);
+        let lambda = -(velocity.dot(&normal) + beta / h * error) / (inv_mass + gamma);
+        // only push back toward the allowed range.
+        let lambda = if error > 0.0 {
+            lambda.min(0.0)
+        } else {
+            lambda.max(0.0)
+        };
+
+        let impulse = normal * lambda;
+        body1.apply_impulse_point_world(&(-impulse), &point1);
+        if let Some(body2) = body2 {
+            body2.apply_impulse_point_world(&impulse, &point2);
+        }
+    }
+}
+
+/// Returns the inverse of the effective mass of the body along the direction at r.
+fn inv_effective_mass(body: &RigidBody, r: &Vector3, dir: &Vector3) -> Float {
+    let angular = (body.get_inv_inertia_tensor_world() * r.cross(dir)).cross(r);
+    body.get_inv_mass() + angular.dot(dir)
+}
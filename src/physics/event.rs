@@ -0,0 +1,24 @@
+use super::*;
+use crate::math::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionEventKind {
+    /// The bodies touched in this step and didn't in the previous one.
+    Started,
+    /// The bodies touched in this step and in the previous one.
+    Persisted,
+    /// The bodies touched in the previous step and don't anymore.
+    Ended,
+}
+
+/// A change in the contact between two bodies during a step of the world.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CollisionEvent {
+    pub kind: CollisionEventKind,
+    pub body_a: RigidBodyId,
+    pub body_b: RigidBodyId,
+    /// Total normal impulse between the bodies in the step, 0 when the contact ended.
+    pub impulse: Float,
+    /// Average contact point in world coordinates, or the last one when the contact ended.
+    pub point: Point3,
+}
@@ -0,0 +1,518 @@
+use crate::math::*;
+use crate::physics::*;
+
+const GJK_MAX_ITERATIONS: usize = 64;
+const GJK_TOLERANCE: Float = 1.0e-5;
+const GJK_EPSILON: Float = 1.0e-10;
+const EPA_MAX_ITERATIONS: usize = 64;
+const EPA_TOLERANCE: Float = 1.0e-4;
+const EPA_EPSILON: Float = 1.0e-6;
+
+/// A point on the Minkowski difference of two shapes.
+/// Every point is in shape1's coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SupportPoint {
+    pub point: Vector3,
+    pub point1: Point3,
+    pub point2: Point3,
+}
+
+impl SupportPoint {
+    /// Returns the supporting point of shape1 - shape2 along dir.
+    /// pos12 is shape2's position relative to shape1.
+    pub fn new(shape1: &dyn Shape, shape2: &dyn Shape, pos12: &Isometry3, dir: &Vector3) -> Self {
+        let dir = UnitVector3::new_normalize(*dir);
+        let point1 = shape1.supporting_point(&dir, 0.0);
+        let dir2 = pos12.inverse_transform_unit_vector(&-dir);
+        let point2 = shape2.supporting_point_world(&dir2, 0.0, pos12);
+        Self {
+            point: point1 - point2,
+            point1,
+            point2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Simplex {
+    points: [SupportPoint; 4],
+    weights: [Float; 4],
+    len: usize,
+}
+
+impl Simplex {
+    fn new(point: SupportPoint) -> Self {
+        Self {
+            points: [point; 4],
+            weights: [1.0, 0.0, 0.0, 0.0],
+            len: 1,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn points(&self) -> &[SupportPoint] {
+        &self.points[..self.len]
+    }
+
+    fn push(&mut self, point: SupportPoint) {
+        self.points[self.len] = point;
+        self.len += 1;
+    }
+
+    fn contains(&self, point: &SupportPoint) -> bool {
+        self.points()
+            .iter()
+            .any(|p| (p.point - point.point).magnitude_squared() < GJK_EPSILON)
+    }
+
+    fn set(&mut self, points: &[(SupportPoint, Float)]) {
+        for (i, (point, weight)) in points.iter().enumerate() {
+            self.points[i] = *point;
+            self.weights[i] = *weight;
+        }
+        self.len = points.len();
+    }
+
+    /// Returns the closest points of the two shapes in shape1's coordinates.
+    fn closest_points(&self) -> (Point3, Point3) {
+        let mut point1 = Vector3::zeros();
+        let mut point2 = Vector3::zeros();
+        for (p, w) in self.points().iter().zip(self.weights.iter()) {
+            point1 += p.point1.coords * *w;
+            point2 += p.point2.coords * *w;
+        }
+        (point1.into(), point2.into())
+    }
+
+    /// Reduces the simplex to the smallest sub simplex containing the point closest to the origin,
+    /// and returns the closest point.
+    fn reduce(&mut self) -> Vector3 {
+        match self.len {
+            1 => {
+                self.weights[0] = 1.0;
+                self.points[0].point
+            }
+            2 => self.reduce_segment(self.points[0], self.points[1]),
+            3 => self.reduce_triangle(self.points[0], self.points[1], self.points[2]),
+            _ => self.reduce_tetrahedron(),
+        }
+    }
+
+    fn reduce_segment(&mut self, a: SupportPoint, b: SupportPoint) -> Vector3 {
+        let ab = b.point - a.point;
+        let denom = ab.magnitude_squared();
+        let t = if denom > GJK_EPSILON {
+            -a.point.dot(&ab) / denom
+        } else {
+            0.0
+        };
+        if t <= 0.0 {
+            self.set(&[(a, 1.0)]);
+            a.point
+        } else if t >= 1.0 {
+            self.set(&[(b, 1.0)]);
+            b.point
+        } else {
+            self.set(&[(a, 1.0 - t), (b, t)]);
+            a.point + ab * t
+        }
+    }
+
+    fn reduce_triangle(&mut self, a: SupportPoint, b: SupportPoint, c: SupportPoint) -> Vector3 {
+        let ab = b.point - a.point;
+        let ac = c.point - a.point;
+        let d1 = -ab.dot(&a.point);
+        let d2 = -ac.dot(&a.point);
+        if d1 <= 0.0 && d2 <= 0.0 {
+            self.set(&[(a, 1.0)]);
+            return a.point;
+        }
+        let d3 = -ab.dot(&b.point);
+        let d4 = -ac.dot(&b.point);
+        if d3 >= 0.0 && d4 <= d3 {
+            self.set(&[(b, 1.0)]);
+            return b.point;
+        }
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+            let v = d1 / (d1 - d3);
+            self.set(&[(a, 1.0 - v), (b, v)]);
+            return a.point + ab * v;
+        }
+        let d5 = -ab.dot(&c.point);
+        let d6 = -ac.dot(&c.point);
+        if d6 >= 0.0 && d5 <= d6 {
+            self.set(&[(c, 1.0)]);
+            return c.point;
+        }
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+            let w = d2 / (d2 - d6);
+            self.set(&[(a, 1.0 - w), (c, w)]);
+            return a.point + ac * w;
+        }
+        let va = d3 * d6 - d5 * d4;
+        if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+            let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+            self.set(&[(b, 1.0 - w), (c, w)]);
+            return b.point + (c.point - b.point) * w;
+        }
+        let sum = va + vb + vc;
+        if sum.abs() < GJK_EPSILON {
+            // degenerate triangle, use the closest edge.
+            let mut best = Simplex::new(a);
+            let mut best_v = best.reduce_segment(a, b);
+            for (p, q) in [(a, c), (b, c)] {
+                let mut s = Simplex::new(p);
+                let v = s.reduce_segment(p, q);
+                if v.magnitude_squared() < best_v.magnitude_squared() {
+                    best = s;
+                    best_v = v;
+                }
+            }
+            *self = best;
+            return best_v;
+        }
+        let v = vb / sum;
+        let w = vc / sum;
+        self.set(&[(a, 1.0 - v - w), (b, v), (c, w)]);
+        a.point + ab * v + ac * w
+    }
+
+    fn reduce_tetrahedron(&mut self) -> Vector3 {
+        let [a, b, c, d] = self.points;
+        let faces = [(a, b, c, d), (a, c, d, b), (a, d, b, c), (b, d, c, a)];
+        let volume = (b.point - a.point).dot(&(c.point - a.point).cross(&(d.point - a.point)));
+        let degenerate = volume.abs() < GJK_EPSILON;
+
+        let mut best: Option<(Simplex, Vector3)> = None;
+        for (p, q, r, s) in faces {
+            let normal = (q.point - p.point).cross(&(r.point - p.point));
+            let sign_origin = -p.point.dot(&normal);
+            let sign_other = (s.point - p.point).dot(&normal);
+            // skip the face if the origin is on the same side as the remaining vertex.
+            if !degenerate && sign_origin * sign_other >= 0.0 {
+                continue;
+            }
+            let mut simplex = Simplex::new(p);
+            let v = simplex.reduce_triangle(p, q, r);
+            let closer = match best {
+                Some((_, best_v)) => v.magnitude_squared() < best_v.magnitude_squared(),
+                None => true,
+            };
+            if closer {
+                best = Some((simplex, v));
+            }
+        }
+        match best {
+            Some((simplex, v)) => {
+                *self = simplex;
+                v
+            }
+            // origin is inside the tetrahedron.
+            None => Vector3::zeros(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum GjkResult {
+    /// The shapes intersect. The simplex encloses or touches the origin.
+    Intersection(Simplex),
+    /// The shapes are apart. point1 is in shape1's coordinates and point2 is in shape2's coordinates.
+    Separation {
+        point1: Point3,
+        point2: Point3,
+        distance: Float,
+    },
+}
+
+/// Runs GJK on two convex shapes.
+/// pos12 is shape2's position relative to shape1.
+pub fn gjk(shape1: &dyn Shape, shape2: &dyn Shape, pos12: &Isometry3) -> GjkResult {
+    let initial_dir = if pos12.translation.vector.magnitude_squared() > GJK_EPSILON {
+        pos12.translation.vector
+    } else {
+        Vector3::x()
+    };
+    let mut simplex = Simplex::new(SupportPoint::new(shape1, shape2, pos12, &initial_dir));
+    let mut v = simplex.points[0].point;
+
+    for _ in 0..GJK_MAX_ITERATIONS {
+        let vv = v.magnitude_squared();
+        if vv < GJK_EPSILON {
+            return GjkResult::Intersection(simplex);
+        }
+        let w = SupportPoint::new(shape1, shape2, pos12, &-v);
+        // no more progress toward the origin.
+        if vv - v.dot(&w.point) <= GJK_TOLERANCE * vv || simplex.contains(&w) {
+            break;
+        }
+        simplex.push(w);
+        v = simplex.reduce();
+        if simplex.len() == 4 {
+            return GjkResult::Intersection(simplex);
+        }
+    }
+
+    let (point1, point2) = simplex.closest_points();
+    GjkResult::Separation {
+        point1,
+        point2: pos12.inverse_transform_point(&point2),
+        distance: v.magnitude(),
+    }
+}
+
+/// Penetration information computed by EPA.
+/// normal is in shape1's coordinates and points from shape1 to shape2.
+/// point1 is in shape1's coordinates and point2 is in shape2's coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Penetration {
+    pub point1: Point3,
+    pub point2: Point3,
+    pub normal: UnitVector3,
+    pub depth: Float,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct EpaFace {
+    indices: [usize; 3],
+    normal: Vector3,
+    distance: Float,
+}
+
+impl EpaFace {
+    fn new(vertices: &[SupportPoint], indices: [usize; 3]) -> Option<Self> {
+        let [a, b, c] = indices.map(|i| vertices[i].point);
+        let normal = (b - a).cross(&(c - a)).try_normalize(EPA_EPSILON)?;
+        let distance = normal.dot(&a);
+        // faces of degenerate support points can't be the closest.
+        if !distance.is_finite() {
+            return None;
+        }
+        Some(Self {
+            indices,
+            normal,
+            distance,
+        })
+    }
+}
+
+/// Expands a GJK simplex into a tetrahedron enclosing the origin.
+fn blow_up_simplex(
+    shape1: &dyn Shape,
+    shape2: &dyn Shape,
+    pos12: &Isometry3,
+    simplex: &Simplex,
+) -> Option<Vec<SupportPoint>> {
+    let support = |dir: &Vector3| SupportPoint::new(shape1, shape2, pos12, dir);
+    let mut vertices = simplex.points().to_vec();
+
+    if vertices.len() == 1 {
+        let axes = [
+            Vector3::x(),
+            -Vector3::x(),
+            Vector3::y(),
+            -Vector3::y(),
+            Vector3::z(),
+            -Vector3::z(),
+        ];
+        let a = vertices[0].point;
+        let w = axes
+            .iter()
+            .map(support)
+            .find(|w| (w.point - a).magnitude_squared() > EPA_EPSILON)?;
+        vertices.push(w);
+    }
+
+    if vertices.len() == 2 {
+        let a = vertices[0].point;
+        let ab = vertices[1].point - a;
+        let axis = if ab.x.abs() < ab.y.abs() && ab.x.abs() < ab.z.abs() {
+            Vector3::x()
+        } else if ab.y.abs() < ab.z.abs() {
+            Vector3::y()
+        } else {
+            Vector3::z()
+        };
+        let mut dir = ab.cross(&axis);
+        let rotation = UnitQuaternion::from_axis_angle(&UnitVector3::new_normalize(ab), PI / 3.0);
+        let mut found = None;
+        for _ in 0..6 {
+            let w = support(&dir);
+            if (w.point - a).cross(&ab).magnitude_squared() > EPA_EPSILON {
+                found = Some(w);
+                break;
+            }
+            dir = rotation * dir;
+        }
+        vertices.push(found?);
+    }
+
+    if vertices.len() == 3 {
+        let a = vertices[0].point;
+        let normal = (vertices[1].point - a).cross(&(vertices[2].point - a));
+        let w = [normal, -normal]
+            .iter()
+            .map(support)
+            .find(|w| (w.point - a).dot(&normal).abs() > EPA_EPSILON)?;
+        vertices.push(w);
+    }
+
+    Some(vertices)
+}
+
+/// Runs EPA on the simplex returned by GJK and returns penetration information.
+/// pos12 is shape2's position relative to shape1.
+pub fn epa(
+    shape1: &dyn Shape,
+    shape2: &dyn Shape,
+    pos12: &Isometry3,
+    simplex: &Simplex,
+) -> Option<Penetration> {
+    let mut vertices = blow_up_simplex(shape1, shape2, pos12, simplex)?;
+
+    // build the initial tetrahedron with outward faces.
+    let mut faces = Vec::new();
+    for (indices, other) in [
+        ([0, 1, 2], 3),
+        ([0, 2, 3], 1),
+        ([0, 3, 1], 2),
+        ([1, 3, 2], 0),
+    ] {
+        let mut face = EpaFace::new(&vertices, indices)?;
+        if face
+            .normal
+            .dot(&(vertices[other].point - vertices[indices[0]].point))
+            > 0.0
+        {
+            face = EpaFace::new(&vertices, [indices[0], indices[2], indices[1]])?;
+        }
+        faces.push(face);
+    }
+
+    let mut closest = faces[0];
+    for _ in 0..EPA_MAX_ITERATIONS {
+        closest = *faces
+            .iter()
+            .min_by(|f1, f2| f1.distance.total_cmp(&f2.distance))?;
+        let w = SupportPoint::new(shape1, shape2, pos12, &closest.normal);
+        if w.point.dot(&closest.normal) - closest.distance < EPA_TOLERANCE {
+            break;
+        }
+
+        let index = vertices.len();
+        vertices.push(w);
+        // remove faces visible from the new point and collect the horizon.
+        let mut horizon: Vec<(usize, usize)> = Vec::new();
+        faces.retain(|face| {
+            let visible = face
+                .normal
+                .dot(&(w.point - vertices[face.indices[0]].point))
+                > EPA_EPSILON;
+            if visible {
+                for k in 0..3 {
+                    let edge = (face.indices[k], face.indices[(k + 1) % 3]);
+                    match horizon.iter().position(|e| *e == (edge.1, edge.0)) {
+                        Some(i) => {
+                            horizon.swap_remove(i);
+                        }
+                        None => horizon.push(edge),
+                    }
+                }
+            }
+            !visible
+        });
+        for (i, j) in horizon {
+            if let Some(face) = EpaFace::new(&vertices, [i, j, index]) {
+                faces.push(face);
+            }
+        }
+        if faces.is_empty() {
+            return None;
+        }
+    }
+
+    // project the origin onto the closest face and interpolate the witness points.
+    let [a, b, c] = closest.indices.map(|i| vertices[i]);
+    let p = closest.normal * closest.distance;
+    let (u, v, w) = barycentric(&p, &a.point, &b.point, &c.point);
+    let point1 = a.point1.coords * u + b.point1.coords * v + c.point1.coords * w;
+    let point2 = a.point2.coords * u + b.point2.coords * v + c.point2.coords * w;
+    Some(Penetration {
+        point1: point1.into(),
+        point2: pos12.inverse_transform_point(&point2.into()),
+        normal: UnitVector3::new_unchecked(closest.normal),
+        depth: closest.distance,
+    })
+}
+
+/// Returns barycentric coordinates of p with respect to the triangle abc.
+fn barycentric(p: &Vector3, a: &Vector3, b: &Vector3, c: &Vector3) -> (Float, Float, Float) {
+    let v0 = b - a;
+    let v1 = c - a;
+    let v2 = p - a;
+    let d00 = v0.dot(&v0);
+    let d01 = v0.dot(&v1);
+    let d11 = v1.dot(&v1);
+    let d20 = v2.dot(&v0);
+    let d21 = v2.dot(&v1);
+    let denom = d00 * d11 - d01 * d01;
+    if denom.abs() < GJK_EPSILON {
+        return (1.0, 0.0, 0.0);
+    }
+    let v = (d11 * d20 - d01 * d21) / denom;
+    let w = (d00 * d21 - d01 * d20) / denom;
+    (1.0 - v - w, v, w)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gjk_separation() {
+        let s1 = Sphere::new(1.0);
+        let s2 = Sphere::new(0.5);
+        let pos12 = Isometry3::translation(3.0, 0.0, 0.0);
+        match gjk(&s1, &s2, &pos12) {
+            GjkResult::Separation {
+                point1,
+                point2,
+                distance,
+            } => {
+                assert!((distance - 1.5).abs() < 1.0e-3);
+                assert!((point1 - Point3::new(1.0, 0.0, 0.0)).magnitude() < 1.0e-3);
+                assert!((point2 - Point3::new(-0.5, 0.0, 0.0)).magnitude() < 1.0e-3);
+            }
+            GjkResult::Intersection(_) => panic!("shapes should be apart"),
+        }
+
+        let c1 = Cube::new(Vector3::new(1.0, 1.0, 1.0));
+        let c2 = Cube::new(Vector3::new(1.0, 1.0, 1.0));
+        let pos12 = Isometry3::translation(0.0, 2.5, 0.0);
+        match gjk(&c1, &c2, &pos12) {
+            GjkResult::Separation { distance, .. } => assert!((distance - 0.5).abs() < 1.0e-3),
+            GjkResult::Intersection(_) => panic!("shapes should be apart"),
+        }
+    }
+
+    #[test]
+    fn test_epa() {
+        let c1 = Cube::new(Vector3::new(1.0, 1.0, 1.0));
+        let c2 = Cube::new(Vector3::new(0.5, 0.5, 0.5));
+        let pos12 = Isometry3::translation(0.0, 1.3, 0.0);
+        let simplex = match gjk(&c1, &c2, &pos12) {
+            GjkResult::Intersection(simplex) => simplex,
+            GjkResult::Separation { .. } => panic!("shapes should intersect"),
+        };
+        let penetration = epa(&c1, &c2, &pos12, &simplex).unwrap();
+        assert!((penetration.depth - 0.2).abs() < 1.0e-3);
+        assert!((penetration.normal.into_inner() - Vector3::y()).magnitude() < 1.0e-3);
+        assert!((penetration.point1.y - 1.0).abs() < 1.0e-3);
+        assert!((penetration.point2.y + 0.5).abs() < 1.0e-3);
+    }
+}
@@ -1,3 +1,4 @@
+mod contains_point;
 mod intersect_sphere_sphere;
 
-pub use self::intersect_sphere_sphere::*;
+pub use self::{contains_point::*, intersect_sphere_sphere::*};
@@ -0,0 +1,74 @@
+use crate::math::*;
+use crate::physics::*;
+
+// Points below are in the shape's local coordinates.
+
+pub fn contains_point_sphere(sphere: &Sphere, point: &Point3) -> bool {
+    point.coords.magnitude_squared() <= sphere.radius * sphere.radius
+}
+
+pub fn contains_point_cube(cube: &Cube, point: &Point3) -> bool {
+    (0..3).all(|i| point[i].abs() <= cube.half_extents[i])
+}
+
+pub fn contains_point_capsule(capsule: &Capsule, point: &Point3) -> bool {
+    let y = point.y.clamp(-capsule.half_height, capsule.half_height);
+    let offset = point - Point3::new(0.0, y, 0.0);
+    offset.magnitude_squared() <= capsule.radius * capsule.radius
+}
+
+pub fn contains_point_convex_hull(hull: &ConvexHull, point: &Point3) -> bool {
+    let vertices = hull.vertices();
+    hull.faces().iter().all(|&[a, b, c]| {
+        match Triangle::new(vertices[a], vertices[b], vertices[c]).normal() {
+            Some(normal) => normal.dot(&(point - vertices[a])) <= 0.0,
+            None => true,
+        }
+    })
+}
+
+/// The plane is solid below its surface.
+pub fn contains_point_plane(plane: &Plane, point: &Point3) -> bool {
+    plane.distance(point) <= 0.0
+}
+
+pub fn contains_point_compound(compound: &Compound, point: &Point3) -> bool {
+    compound.children().iter().any(|child| {
+        let point = child.position.inverse_transform_point(point);
+        child.shape.contains_point(&point)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_contains_point() {
+        let inside = Point3::new(0.4, -0.9, 0.2);
+        let outside = Point3::new(0.4, -1.1, 0.2);
+        let cube = Cube::new(Vector3::new(0.5, 1.0, 0.5));
+        assert!(contains_point_cube(&cube, &inside));
+        assert!(!contains_point_cube(&cube, &outside));
+        let hull = ConvexHull::new(&cube.corners()).unwrap();
+        assert!(contains_point_convex_hull(&hull, &inside));
+        assert!(!contains_point_convex_hull(&hull, &outside));
+
+        let sphere = Sphere::new(1.1);
+        assert!(contains_point_sphere(&sphere, &inside));
+        assert!(!contains_point_sphere(&sphere, &outside));
+        let capsule = Capsule::new(0.5, 1.0);
+        assert!(contains_point_capsule(
+            &capsule,
+            &Point3::new(0.0, 1.4, 0.0)
+        ));
+        assert!(!contains_point_capsule(
+            &capsule,
+            &Point3::new(0.4, 1.4, 0.0)
+        ));
+
+        let plane = Plane::new(Vector3::y_axis(), -1.0);
+        assert!(contains_point_plane(&plane, &outside));
+        assert!(!contains_point_plane(&plane, &inside));
+    }
+}
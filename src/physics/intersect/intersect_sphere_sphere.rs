@@ -1,8 +1,29 @@
 use crate::math::*;
 
 use crate::physics::*;
+/// Returns true if the spheres overlap or touch.
 pub fn intersect_sphere_sphere(s1: &Sphere, s2: &Sphere, pos_12: &Isometry3) -> bool {
     let d2 = pos_12.translation.vector.magnitude_squared();
     let r = s1.radius + s2.radius;
-    d2 >= r * r
+    d2 <= r * r
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_intersect_sphere_sphere() {
+        let (s1, s2) = (Sphere::new(1.0), Sphere::new(0.5));
+        assert!(intersect_sphere_sphere(
+            &s1,
+            &s2,
+            &Isometry3::translation(1.0, 1.0, 0.0)
+        ));
+        assert!(!intersect_sphere_sphere(
+            &s1,
+            &s2,
+            &Isometry3::translation(1.2, 1.0, 0.0)
+        ));
+    }
 }
@@ -0,0 +1,50 @@
+/// Groups of bodies connected by contacts, found with union-find.
+/// Bodies are identified by their slot in the world's arena.
+#[derive(Debug, Clone)]
+pub struct Islands {
+    parents: Vec<usize>,
+}
+
+impl Islands {
+    /// Every body starts in its own island.
+    pub fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+        }
+    }
+
+    /// Returns the representative body of the island of i.
+    pub fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            // path halving keeps the trees flat.
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+        i
+    }
+
+    /// Merges the islands of a and b.
+    pub fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parents[a.max(b)] = a.min(b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_islands() {
+        let mut islands = Islands::new(6);
+        islands.union(0, 1);
+        islands.union(3, 4);
+        islands.union(4, 1);
+        assert_eq!(islands.find(3), islands.find(0));
+        assert_eq!(islands.find(4), islands.find(1));
+        assert_ne!(islands.find(2), islands.find(0));
+        assert_ne!(islands.find(5), islands.find(2));
+    }
+}
@@ -0,0 +1,156 @@
+use super::*;
+use crate::math::*;
+
+/// Drives a revolute joint at a target angular velocity with a limited torque.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointMotor {
+    pub target_velocity: Float,
+    pub max_torque: Float,
+}
+
+/// The ways a joint lets its bodies move. The axis of a joint is the x axis of its frames.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JointType {
+    /// Keeps the anchors together, the bodies rotate freely around them.
+    Spherical,
+    /// Keeps the anchors together and lets the bodies rotate only around the axis.
+    /// The angle is limited to (lower, upper) in radians.
+    Revolute {
+        limits: Option<(Float, Float)>,
+        motor: Option<JointMotor>,
+    },
+    /// Keeps the frames aligned and lets the bodies slide only along the axis.
+    /// The translation is limited to (lower, upper).
+    Prismatic { limits: Option<(Float, Float)> },
+    /// Keeps the frames together.
+    Weld,
+}
+
+/// Connects two bodies. Each body has a frame in its own coordinates,
+/// and the joint keeps the frames together except for the motion its type allows.
+#[derive(Debug, Clone)]
+pub struct Joint {
+    joint_type: JointType,
+    body1: RigidBodyId,
+    body2: RigidBodyId,
+    frame1: Isometry3,
+    frame2: Isometry3,
+    /// Contacts between the connected bodies are ignored unless this is set.
+    contacts_enabled: bool,
+}
+
+impl Joint {
+    pub fn new(
+        joint_type: JointType,
+        body1: RigidBodyId,
+        frame1: Isometry3,
+        body2: RigidBodyId,
+        frame2: Isometry3,
+    ) -> Self {
+        Self {
+            joint_type,
+            body1,
+            body2,
+            frame1,
+            frame2,
+            contacts_enabled: false,
+        }
+    }
+
+    /// Creates a joint whose frames are both at the frame in world coordinates,
+    /// with the bodies where they are now.
+    pub fn new_world(
+        joint_type: JointType,
+        (id1, b1): (RigidBodyId, &RigidBody),
+        (id2, b2): (RigidBodyId, &RigidBody),
+        frame: &Isometry3,
+    ) -> Self {
+        let frame1 = b1.get_position().inverse() * frame;
+        let frame2 = b2.get_position().inverse() * frame;
+        Self::new(joint_type, id1, frame1, id2, frame2)
+    }
+
+    /// Returns a frame at the point whose x axis is the axis.
+    pub fn axis_frame(point: &Point3, axis: &UnitVector3) -> Isometry3 {
+        let rotation = UnitQuaternion::rotation_between_axis(&Vector3::x_axis(), axis)
+            .unwrap_or_else(|| UnitQuaternion::from_axis_angle(&Vector3::y_axis(), PI));
+        Isometry3::from_parts(Translation3::from(point.coords), rotation)
+    }
+
+    pub fn get_type(&self) -> &JointType {
+        &self.joint_type
+    }
+    pub fn set_type(&mut self, joint_type: JointType) {
+        self.joint_type = joint_type;
+    }
+    pub fn get_bodies(&self) -> (RigidBodyId, RigidBodyId) {
+        (self.body1, self.body2)
+    }
+    pub fn get_frame1(&self) -> &Isometry3 {
+        &self.frame1
+    }
+    pub fn get_frame2(&self) -> &Isometry3 {
+        &self.frame2
+    }
+    pub fn get_contacts_enabled(&self) -> bool {
+        self.contacts_enabled
+    }
+    pub fn set_contacts_enabled(&mut self, enabled: bool) {
+        self.contacts_enabled = enabled;
+    }
+
+    /// Returns the frames in world coordinates.
+    pub fn world_frames(&self, b1: &RigidBody, b2: &RigidBody) -> (Isometry3, Isometry3) {
+        (
+            b1.get_position() * self.frame1,
+            b2.get_position() * self.frame2,
+        )
+    }
+
+    /// Returns the angle of frame2 around the axis of frame1, in (-PI, PI].
+    pub fn angle(&self, b1: &RigidBody, b2: &RigidBody) -> Float {
+        let (f1, f2) = self.world_frames(b1, b2);
+        let axis = f1.rotation * Vector3::x();
+        let y1 = f1.rotation * Vector3::y();
+        let y2 = f2.rotation * Vector3::y();
+        Float::atan2(axis.dot(&y1.cross(&y2)), y1.dot(&y2))
+    }
+
+    /// Returns the offset of frame2 from frame1 along the axis of frame1.
+    pub fn translation(&self, b1: &RigidBody, b2: &RigidBody) -> Float {
+        let (f1, f2) = self.world_frames(b1, b2);
+        let axis = f1.rotation * Vector3::x();
+        (f2.translation.vector - f1.translation.vector).dot(&axis)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_joint_measures() {
+        let mut world = PhysicsWorld::new();
+        let shape = || ShapeType::Cube(Cube::new(Vector3::new(0.5, 0.5, 0.5)));
+        let id1 = world.insert(RigidBody::new(shape(), 0.0));
+        let mut b2 = RigidBody::new(shape(), 1.0);
+        b2.set_position(&Isometry3::translation(0.0, 2.0, 0.0));
+        let id2 = world.insert(b2);
+
+        let frame = Joint::axis_frame(&Point3::new(0.0, 1.0, 0.0), &Vector3::y_axis());
+        let joint = {
+            let (b1, b2) = (world.get(id1).unwrap(), world.get(id2).unwrap());
+            Joint::new_world(JointType::Weld, (id1, b1), (id2, b2), &frame)
+        };
+
+        // turn and lift body2 around the axis.
+        let b2 = world.get_mut(id2).unwrap();
+        b2.set_position(&Isometry3::new(
+            Vector3::new(0.0, 2.5, 0.0),
+            Vector3::new(0.0, PI / 3.0, 0.0),
+        ));
+        let (b1, b2) = (world.get(id1).unwrap(), world.get(id2).unwrap());
+        assert!((joint.angle(b1, b2) - PI / 3.0).abs() < 1.0e-5);
+        assert!((joint.translation(b1, b2) - 0.5).abs() < 1.0e-5);
+    }
+}
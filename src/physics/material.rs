@@ -0,0 +1,128 @@
+use crate::math::*;
+
+/// How the coefficients of two materials in contact are combined.
+/// When the materials use different modes, the one later in this list wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CombineMode {
+    Average,
+    Min,
+    Multiply,
+    Max,
+}
+
+impl CombineMode {
+    pub fn combine(self, a: Float, b: Float) -> Float {
+        match self {
+            CombineMode::Average => (a + b) * 0.5,
+            CombineMode::Min => a.min(b),
+            CombineMode::Multiply => a * b,
+            CombineMode::Max => a.max(b),
+        }
+    }
+}
+
+/// Surface properties of a body.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhysicsMaterial {
+    /// Friction of surfaces at rest relative to each other.
+    pub static_friction: Float,
+    /// Friction of sliding surfaces.
+    pub dynamic_friction: Float,
+    /// Fraction of the normal speed kept by a bounce.
+    pub restitution: Float,
+    /// Torque against rolling per unit of normal force.
+    pub rolling_resistance: Float,
+    /// Combines the frictions.
+    pub friction_combine: CombineMode,
+    pub restitution_combine: CombineMode,
+    /// Combines the rolling resistances. Max by default, so that a body keeps its resistance
+    /// on surfaces which have none.
+    pub rolling_resistance_combine: CombineMode,
+}
+
+impl PhysicsMaterial {
+    pub fn new(friction: Float, restitution: Float) -> Self {
+        Self {
+            static_friction: friction,
+            dynamic_friction: friction,
+            restitution,
+            rolling_resistance: 0.0,
+            friction_combine: CombineMode::Multiply,
+            restitution_combine: CombineMode::Multiply,
+            rolling_resistance_combine: CombineMode::Max,
+        }
+    }
+
+    /// Returns the material of a contact between the materials.
+    pub fn combine(&self, other: &Self) -> Self {
+        let friction_combine = self.friction_combine.max(other.friction_combine);
+        let restitution_combine = self.restitution_combine.max(other.restitution_combine);
+        let rolling_resistance_combine = self
+            .rolling_resistance_combine
+            .max(other.rolling_resistance_combine);
+        let friction = |a: Float, b: Float| friction_combine.combine(a, b);
+        Self {
+            static_friction: friction(self.static_friction, other.static_friction),
+            dynamic_friction: friction(self.dynamic_friction, other.dynamic_friction),
+            restitution: restitution_combine.combine(self.restitution, other.restitution),
+            rolling_resistance: rolling_resistance_combine
+                .combine(self.rolling_resistance, other.rolling_resistance),
+            friction_combine,
+            restitution_combine,
+            rolling_resistance_combine,
+        }
+    }
+}
+
+impl Default for PhysicsMaterial {
+    fn default() -> Self {
+        Self::new(0.3, 0.8)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_combine() {
+        let ice = PhysicsMaterial {
+            static_friction: 0.1,
+            dynamic_friction: 0.03,
+            restitution: 0.1,
+            friction_combine: CombineMode::Min,
+            ..PhysicsMaterial::default()
+        };
+        let rubber = PhysicsMaterial {
+            static_friction: 1.0,
+            dynamic_friction: 0.8,
+            restitution: 0.9,
+            rolling_resistance: 0.02,
+            friction_combine: CombineMode::Max,
+            restitution_combine: CombineMode::Max,
+            rolling_resistance_combine: CombineMode::Max,
+        };
+
+        let default = PhysicsMaterial::default().combine(&PhysicsMaterial::default());
+        assert!((default.dynamic_friction - 0.09).abs() < 1.0e-6);
+        assert!((default.restitution - 0.64).abs() < 1.0e-6);
+        // max wins over min and multiply.
+        let contact = ice.combine(&rubber);
+        assert_eq!(contact, rubber.combine(&ice));
+        assert_eq!(contact.friction_combine, CombineMode::Max);
+        assert_eq!(contact.static_friction, 1.0);
+        assert_eq!(contact.restitution, 0.9);
+        let contact = ice.combine(&PhysicsMaterial::default());
+        assert_eq!(contact.friction_combine, CombineMode::Multiply);
+        assert!((contact.dynamic_friction - 0.009).abs() < 1.0e-6);
+        // rolling resistance isn't lost on surfaces without any.
+        let ball = PhysicsMaterial {
+            rolling_resistance: 0.05,
+            ..PhysicsMaterial::default()
+        };
+        let contact = ball.combine(&PhysicsMaterial::default());
+        assert_eq!(contact.rolling_resistance, 0.05);
+        assert_eq!(CombineMode::Average.combine(0.2, 0.4), 0.3);
+        assert_eq!(CombineMode::Min.combine(0.2, 0.4), 0.2);
+    }
+}
@@ -0,0 +1,318 @@
+use crate::math::*;
+use crate::physics::*;
+
+/// A body hit by a ray. Vectors are in world coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub body: RigidBodyId,
+    pub point: Point3,
+    /// Normal of the surface at the point, facing the ray.
+    pub normal: UnitVector3,
+    /// Distance along the ray.
+    pub distance: Float,
+}
+
+// Ray casts below are in the shape's local coordinates. They return the distance
+// along the ray to the first hit in [0, max_toi] and the normal of the surface there.
+// Rays starting inside a solid shape hit it at 0 with the normal against the ray.
+
+pub fn cast_ray_sphere(
+    sphere: &Sphere,
+    origin: &Point3,
+    dir: &UnitVector3,
+    max_toi: Float,
+) -> Option<(Float, UnitVector3)> {
+    let b = origin.coords.dot(dir);
+    let c = origin.coords.magnitude_squared() - sphere.radius * sphere.radius;
+    if c <= 0.0 {
+        return Some((0.0, -*dir));
+    }
+    let discriminant = b * b - c;
+    if b > 0.0 || discriminant < 0.0 {
+        return None;
+    }
+    let toi = -b - discriminant.sqrt();
+    if toi > max_toi {
+        return None;
+    }
+    let normal = UnitVector3::new_normalize(origin.coords + dir.into_inner() * toi);
+    Some((toi, normal))
+}
+
+/// Clips the ray by the half spaces `normal . p <= offset` of a convex shape.
+fn cast_ray_half_spaces(
+    planes: impl Iterator<Item = (UnitVector3, Float)>,
+    origin: &Point3,
+    dir: &UnitVector3,
+    max_toi: Float,
+) -> Option<(Float, UnitVector3)> {
+    let (mut enter, mut exit) = (0.0, max_toi);
+    let mut normal = -*dir;
+    for (plane_normal, offset) in planes {
+        let distance = plane_normal.dot(&origin.coords) - offset;
+        let speed = plane_normal.dot(dir);
+        if speed.abs() < 1.0e-7 {
+            if distance > 0.0 {
+                return None;
+            }
+            continue;
+        }
+        let toi = -distance / speed;
+        if speed < 0.0 {
+            if toi > enter {
+                enter = toi;
+                normal = plane_normal;
+            }
+        } else {
+            exit = exit.min(toi);
+        }
+        if enter > exit {
+            return None;
+        }
+    }
+    Some((enter, normal))
+}
+
+pub fn cast_ray_cube(
+    cube: &Cube,
+    origin: &Point3,
+    dir: &UnitVector3,
+    max_toi: Float,
+) -> Option<(Float, UnitVector3)> {
+    let axes = [Vector3::x_axis(), Vector3::y_axis(), Vector3::z_axis()];
+    let planes = (0..3).flat_map(|i| {
+        let extent = cube.half_extents[i];
+        [(axes[i], extent), (-axes[i], extent)]
+    });
+    cast_ray_half_spaces(planes, origin, dir, max_toi)
+}
+
+pub fn cast_ray_convex_hull(
+    hull: &ConvexHull,
+    origin: &Point3,
+    dir: &UnitVector3,
+    max_toi: Float,
+) -> Option<(Float, UnitVector3)> {
+    let vertices = hull.vertices();
+    let planes = hull.faces().iter().filter_map(|&[a, b, c]| {
+        let normal = Triangle::new(vertices[a], vertices[b], vertices[c]).normal()?;
+        Some((normal, normal.dot(&vertices[a].coords)))
+    });
+    cast_ray_half_spaces(planes, origin, dir, max_toi)
+}
+
+pub fn cast_ray_capsule(
+    capsule: &Capsule,
+    origin: &Point3,
+    dir: &UnitVector3,
+    max_toi: Float,
+) -> Option<(Float, UnitVector3)> {
+    let h = capsule.half_height;
+    let r = capsule.radius;
+    let closest_y = origin.y.clamp(-h, h);
+    if (origin - Point3::new(0.0, closest_y, 0.0)).magnitude_squared() <= r * r {
+        return Some((0.0, -*dir));
+    }
+
+    // the side is a cylinder along y.
+    let mut hit = None;
+    let a = dir.x * dir.x + dir.z * dir.z;
+    let b = origin.x * dir.x + origin.z * dir.z;
+    let c = origin.x * origin.x + origin.z * origin.z - r * r;
+    let discriminant = b * b - a * c;
+    if a > 1.0e-7 && discriminant >= 0.0 {
+        let toi = (-b - discriminant.sqrt()) / a;
+        let point = origin + dir.into_inner() * toi;
+        if toi >= 0.0 && toi <= max_toi && point.y.abs() <= h {
+            let normal = UnitVector3::new_normalize(Vector3::new(point.x, 0.0, point.z));
+            hit = Some((toi, normal));
+        }
+    }
+    // the caps are spheres at the ends of the segment.
+    let sphere = Sphere::new(r);
+    for end in [-h, h] {
+        let origin = origin - Vector3::new(0.0, end, 0.0);
+        if let Some((toi, normal)) = cast_ray_sphere(&sphere, &origin, dir, max_toi) {
+            if hit.is_none_or(|(first, _)| toi < first) {
+                hit = Some((toi, normal));
+            }
+        }
+    }
+    hit
+}
+
+/// The triangle is hit from both sides.
+pub fn cast_ray_triangle(
+    triangle: &Triangle,
+    origin: &Point3,
+    dir: &UnitVector3,
+    max_toi: Float,
+) -> Option<(Float, UnitVector3)> {
+    // Möller-Trumbore.
+    let edge1 = triangle.b - triangle.a;
+    let edge2 = triangle.c - triangle.a;
+    let p = dir.cross(&edge2);
+    let det = edge1.dot(&p);
+    if det.abs() < 1.0e-9 {
+        return None;
+    }
+    let offset = origin - triangle.a;
+    let u = offset.dot(&p) / det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = offset.cross(&edge1);
+    let v = dir.dot(&q) / det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let toi = edge2.dot(&q) / det;
+    if toi < 0.0 || toi > max_toi {
+        return None;
+    }
+    let normal = triangle.normal()?;
+    let normal = if normal.dot(dir) > 0.0 {
+        -normal
+    } else {
+        normal
+    };
+    Some((toi, normal))
+}
+
+/// Returns the bounding box of the ray, cut at max_toi.
+fn ray_aabb(origin: &Point3, dir: &UnitVector3, max_toi: Float) -> AABB {
+    let end = origin + dir.into_inner() * max_toi;
+    AABB::new_min_max(origin.inf(&end), origin.sup(&end))
+}
+
+/// Returns the first hit among the triangles.
+fn cast_ray_triangles(
+    triangles: impl Iterator<Item = Triangle>,
+    origin: &Point3,
+    dir: &UnitVector3,
+    max_toi: Float,
+) -> Option<(Float, UnitVector3)> {
+    triangles
+        .filter_map(|triangle| cast_ray_triangle(&triangle, origin, dir, max_toi))
+        .min_by(|(toi1, _), (toi2, _)| toi1.total_cmp(toi2))
+}
+
+pub fn cast_ray_tri_mesh(
+    mesh: &TriMesh,
+    origin: &Point3,
+    dir: &UnitVector3,
+    max_toi: Float,
+) -> Option<(Float, UnitVector3)> {
+    // don't let an endless ray cover the whole mesh.
+    let max_toi = max_toi.min(mesh.get_local_aabb().clip_ray(origin, dir)?.1);
+    let mut triangles = Vec::new();
+    mesh.query_triangles(&ray_aabb(origin, dir, max_toi), &mut triangles);
+    let triangles = triangles.into_iter().map(|i| mesh.triangle(*i));
+    cast_ray_triangles(triangles, origin, dir, max_toi)
+}
+
+pub fn cast_ray_height_field(
+    field: &HeightField,
+    origin: &Point3,
+    dir: &UnitVector3,
+    max_toi: Float,
+) -> Option<(Float, UnitVector3)> {
+    let max_toi = max_toi.min(field.get_local_aabb().clip_ray(origin, dir)?.1);
+    let (rows, cols) = field.cells_in(&ray_aabb(origin, dir, max_toi))?;
+    let triangles = rows
+        .flat_map(|row| cols.clone().map(move |col| (row, col)))
+        .flat_map(|(row, col)| field.cell_triangles(row, col));
+    cast_ray_triangles(triangles, origin, dir, max_toi)
+}
+
+/// The plane is solid below its surface.
+pub fn cast_ray_plane(
+    plane: &Plane,
+    origin: &Point3,
+    dir: &UnitVector3,
+    max_toi: Float,
+) -> Option<(Float, UnitVector3)> {
+    cast_ray_half_spaces(
+        [(plane.normal, plane.offset)].into_iter(),
+        origin,
+        dir,
+        max_toi,
+    )
+}
+
+pub fn cast_ray_compound(
+    compound: &Compound,
+    origin: &Point3,
+    dir: &UnitVector3,
+    max_toi: Float,
+) -> Option<(Float, UnitVector3)> {
+    compound
+        .children()
+        .iter()
+        .filter_map(|child| {
+            let origin = child.position.inverse_transform_point(origin);
+            let dir = child.position.inverse_transform_unit_vector(dir);
+            let (toi, normal) = child.shape.cast_ray(&origin, &dir, max_toi)?;
+            Some((toi, child.position.rotation * normal))
+        })
+        .min_by(|(toi1, _), (toi2, _)| toi1.total_cmp(toi2))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cast_ray() {
+        let origin = Point3::new(-5.0, 0.5, 0.0);
+        let dir = Vector3::x_axis();
+
+        let sphere = Sphere::new(1.0);
+        let (toi, normal) = cast_ray_sphere(&sphere, &origin, &dir, 10.0).unwrap();
+        assert!((toi - (5.0 - Float::sqrt(0.75))).abs() < 1.0e-5);
+        assert!((normal.y - 0.5).abs() < 1.0e-5);
+        assert_eq!(cast_ray_sphere(&sphere, &origin, &dir, 4.0), None);
+        assert_eq!(cast_ray_sphere(&sphere, &origin, &(-dir), 10.0), None);
+        assert_eq!(
+            cast_ray_sphere(&sphere, &Point3::origin(), &dir, 10.0),
+            Some((0.0, -dir))
+        );
+
+        let cube = Cube::new(Vector3::new(1.0, 1.0, 1.0));
+        let (toi, normal) = cast_ray_cube(&cube, &origin, &dir, 10.0).unwrap();
+        assert!((toi - 4.0).abs() < 1.0e-5);
+        assert_eq!(normal, -dir);
+        let above = Point3::new(-5.0, 1.5, 0.0);
+        assert_eq!(cast_ray_cube(&cube, &above, &dir, 10.0), None);
+
+        let hull = ConvexHull::new(&cube.corners()).unwrap();
+        let (toi, normal) = cast_ray_convex_hull(&hull, &origin, &dir, 10.0).unwrap();
+        assert!((toi - 4.0).abs() < 1.0e-5);
+        assert!((normal.into_inner() + dir.into_inner()).magnitude() < 1.0e-5);
+
+        let capsule = Capsule::new(0.5, 1.0);
+        let (toi, normal) = cast_ray_capsule(&capsule, &origin, &dir, 10.0).unwrap();
+        assert!((toi - 4.5).abs() < 1.0e-5);
+        assert!((normal.into_inner() + dir.into_inner()).magnitude() < 1.0e-5);
+        let top = Point3::new(0.0, 5.0, 0.0);
+        let (toi, normal) = cast_ray_capsule(&capsule, &top, &(-Vector3::y_axis()), 10.0).unwrap();
+        assert!((toi - 3.5).abs() < 1.0e-5);
+        assert_eq!(normal, Vector3::y_axis());
+
+        let triangle = Triangle::new(
+            Point3::new(0.0, -1.0, -1.0),
+            Point3::new(0.0, 1.0, -1.0),
+            Point3::new(0.0, 0.0, 1.0),
+        );
+        let (toi, normal) = cast_ray_triangle(&triangle, &origin, &dir, 10.0).unwrap();
+        assert!((toi - 5.0).abs() < 1.0e-5);
+        assert!(normal.dot(&dir) < 0.0);
+
+        let plane = Plane::new(Vector3::y_axis(), 0.0);
+        let origin = Point3::new(0.0, 3.0, 0.0);
+        let dir = UnitVector3::new_normalize(Vector3::new(1.0, -1.0, 0.0));
+        let (toi, normal) = cast_ray_plane(&plane, &origin, &dir, 10.0).unwrap();
+        assert!((toi - 3.0 * Float::sqrt(2.0)).abs() < 1.0e-5);
+        assert_eq!(normal, Vector3::y_axis());
+    }
+}
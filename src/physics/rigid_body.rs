@@ -1,15 +1,61 @@
 use super::*;
 use crate::math::*;
+
+/// Bodies slower than these can fall asleep.
+const SLEEP_LINEAR_VELOCITY: Float = 0.05;
+const SLEEP_ANGULAR_VELOCITY: Float = 0.05;
+/// Time a body has to stay slow before it falls asleep.
+pub const TIME_TO_SLEEP: Float = 0.5;
+
+/// Collision layers. A body belongs to the layers in its groups,
+/// and collides with the bodies whose groups are in its mask.
+pub const LAYER_DEFAULT: u32 = 1 << 0;
+pub const LAYER_STATIC: u32 = 1 << 1;
+pub const LAYER_PLAYER: u32 = 1 << 2;
+pub const LAYER_ENEMY: u32 = 1 << 3;
+pub const LAYER_DEBRIS: u32 = 1 << 4;
+pub const LAYER_PROJECTILE: u32 = 1 << 5;
+pub const LAYER_ALL: u32 = u32::MAX;
+
+/// How a body is moved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyType {
+    /// Moved by forces and contacts.
+    Dynamic,
+    /// Never moves unless its position is set.
+    Static,
+    /// Moves with its velocities, or toward its kinematic target, whatever it hits.
+    /// Dynamic bodies it pushes are carried along by its velocity.
+    Kinematic,
+}
+
 #[derive(Debug, Clone)]
 pub struct RigidBody {
     position: Isometry3,
+    /// Position before the last step, to interpolate between steps.
+    previous_position: Isometry3,
     linear_velocity: Vector3,
     angular_velocity: Vector3,
     inv_mass: Float,
-    elasticity: Float,
+    body_type: BodyType,
+    /// Position a kinematic body moves to in each step.
+    kinematic_target: Option<Isometry3>,
+    material: PhysicsMaterial,
     damping: Float,
     anguar_damping: Float,
-    friction: Float,
+    /// Continuous collision detection. The body is stopped at its first impact in a step,
+    /// so fast and small bodies don't pass through thin ones.
+    ccd: bool,
+    /// Sensors report overlaps with other bodies but don't collide with them.
+    sensor: bool,
+    /// Layers the body belongs to.
+    collision_groups: u32,
+    /// Layers the body collides with.
+    collision_mask: u32,
+    /// Sleeping bodies aren't simulated until something touches them.
+    sleeping: bool,
+    /// Time the body has been slow enough to sleep.
+    sleep_time: Float,
     shape: ShapeType,
 
     force_accum: Vector3,
@@ -18,55 +64,101 @@ pub struct RigidBody {
 }
 
 impl RigidBody {
+    /// A body with an inv_mass of 0 is static, otherwise it is dynamic.
     pub fn new(shape: ShapeType, inv_mass: Float) -> Self {
         Self {
             position: Isometry3::identity(),
+            previous_position: Isometry3::identity(),
             linear_velocity: Vector3::zeros(),
             angular_velocity: Vector3::zeros(),
             force_accum: Vector3::zeros(),
             torque_accum: Vector3::zeros(),
             shape,
             inv_mass,
+            body_type: if inv_mass == 0.0 {
+                BodyType::Static
+            } else {
+                BodyType::Dynamic
+            },
+            kinematic_target: None,
             damping: 0.99,
             anguar_damping: 0.99,
-            elasticity: 0.8,
-            friction: 0.3,
+            material: PhysicsMaterial::default(),
+            ccd: false,
+            sensor: false,
+            collision_groups: LAYER_DEFAULT,
+            collision_mask: LAYER_ALL,
+            sleeping: false,
+            sleep_time: 0.0,
             moved: false,
         }
     }
 
     /// Calculate new position. Returns true if it moved.
     pub fn update(&mut self, delta_time: Float) -> bool {
-        if self.inv_mass == 0.0 {
-            let moved = self.moved;
-            self.moved = false;
-            return moved;
+        self.integrate_velocity(delta_time);
+        self.integrate_position(delta_time)
+    }
+
+    /// Applies the accumulated force and torque and the damping to the velocities.
+    /// Kinematic bodies ignore forces, and take the velocities which reach their target.
+    pub fn integrate_velocity(&mut self, delta_time: Float) {
+        if self.body_type == BodyType::Kinematic {
+            if let Some(target) = self.kinematic_target {
+                let center = target.transform_point(&self.get_center_of_mass_local());
+                let rotation = target.rotation * self.position.rotation.inverse();
+                self.linear_velocity = (center - self.get_center_of_mass_world()) / delta_time;
+                self.angular_velocity = rotation.scaled_axis() / delta_time;
+            }
+        }
+        if self.get_inv_mass() == 0.0 {
+            self.clear_force();
+            return;
         }
         let acc = self.inv_mass * self.force_accum;
         self.linear_velocity += acc * delta_time;
         self.linear_velocity *= self.damping.powf(delta_time);
-        let delta = self.linear_velocity * delta_time;
-        let mut moved = delta != Vector3::zeros();
-        self.position
-            .append_translation_mut(&Translation3::from(delta));
 
-        // Update angular velocity.
+        // Euler's equation, I * alpha = torque - w x (I * w).
         let rotation = self.position.rotation.to_rotation_matrix().into_inner();
         let inertia_tensor = rotation * self.shape.get_inertia_tensor() * rotation.transpose();
+        let gyroscopic = self
+            .angular_velocity
+            .cross(&(inertia_tensor * self.angular_velocity));
         let alpha = inertia_tensor.try_inverse().unwrap()
-            * (self
-                .angular_velocity
-                .cross(&(inertia_tensor * self.angular_velocity)));
+            * (self.inv_mass * self.torque_accum - gyroscopic);
         self.angular_velocity += alpha * delta_time;
         self.angular_velocity *= self.anguar_damping.powf(delta_time);
-        let delta = self.angular_velocity * delta_time;
-        moved = moved || delta != Vector3::zeros();
+        self.clear_force();
+    }
+
+    /// Moves the body with its velocities. Returns true if it moved.
+    pub fn integrate_position(&mut self, delta_time: Float) -> bool {
+        let moved = self.moved;
+        self.moved = false;
+        if self.body_type == BodyType::Static {
+            return moved;
+        }
+        let translation = self.linear_velocity * delta_time;
+        let rotation = self.angular_velocity * delta_time;
+        let displaced = self.displace(&translation, &rotation);
+        if let (BodyType::Kinematic, Some(target)) = (self.body_type, self.kinematic_target) {
+            // don't let rounding drift the body away from its target.
+            self.position = target;
+        }
+        moved || displaced
+    }
+
+    /// Translates the body and rotates it around its center of mass by the scaled axis.
+    /// Returns true if it moved.
+    pub fn displace(&mut self, translation: &Vector3, rotation: &Vector3) -> bool {
+        self.position
+            .append_translation_mut(&Translation3::from(*translation));
         self.position.append_rotation_wrt_point_mut(
-            &UnitQuaternion::from_scaled_axis(delta),
+            &UnitQuaternion::from_scaled_axis(*rotation),
             &self.get_center_of_mass_world(),
         );
-        self.clear_force();
-        moved
+        *translation != Vector3::zeros() || *rotation != Vector3::zeros()
     }
 
     pub fn apply_force_point_local(&mut self, force: &Vector3, point: &Point3) {
@@ -77,6 +169,7 @@ impl RigidBody {
         self.apply_force_point_world(&force, &point);
     }
     pub fn apply_force_point_world(&mut self, force: &Vector3, point: &Point3) {
+        self.wake_if_sleeping();
         self.force_accum += force;
         let center = self.get_center_of_mass_world();
         let relative_pos = point - center;
@@ -98,47 +191,97 @@ impl RigidBody {
         self.apply_angular_impulse_world(&angular);
     }
     pub fn apply_force_world(&mut self, force: &Vector3) {
+        self.wake_if_sleeping();
         self.force_accum += force;
     }
     pub fn apply_force_local(&mut self, force: &Vector3) {
+        self.wake_if_sleeping();
         let w = self.position.transform_vector(force);
         self.force_accum += w;
     }
 
     pub fn apply_torque_world(&mut self, force: &Vector3) {
+        self.wake_if_sleeping();
         self.torque_accum += force;
     }
     pub fn apply_torque_local(&mut self, force: &Vector3) {
+        self.wake_if_sleeping();
         let force = self.position.transform_vector(force);
         self.torque_accum += force;
     }
 
     pub fn apply_impulse_world(&mut self, impulse: &Vector3) {
-        self.linear_velocity += self.inv_mass * impulse;
+        self.wake_if_sleeping();
+        self.linear_velocity += self.get_inv_mass() * impulse;
     }
     pub fn apply_impulse_local(&mut self, impulse: &Vector3) {
+        self.wake_if_sleeping();
         let w = self.position.transform_vector(impulse);
-        self.linear_velocity += self.inv_mass * w;
+        self.linear_velocity += self.get_inv_mass() * w;
     }
     pub fn apply_angular_impulse_world(&mut self, impulse: &Vector3) {
+        self.wake_if_sleeping();
         self.angular_velocity += self.get_inv_inertia_tensor_world() * impulse;
     }
     pub fn apply_angular_impulse_local(&mut self, impulse: &Vector3) {
+        self.wake_if_sleeping();
         let impulse = self.position.transform_vector(impulse);
         self.angular_velocity += self.get_inv_inertia_tensor_world() * impulse;
     }
 
+    /// Wakes the body up and restarts its sleep timer.
+    pub fn wake(&mut self) {
+        self.sleeping = false;
+        self.sleep_time = 0.0;
+    }
+
+    /// Puts the body to sleep and stops it. Only dynamic bodies sleep.
+    pub fn sleep(&mut self) {
+        if self.body_type != BodyType::Dynamic {
+            return;
+        }
+        self.sleeping = true;
+        self.linear_velocity = Vector3::zeros();
+        self.angular_velocity = Vector3::zeros();
+        self.clear_force();
+    }
+
+    pub fn is_sleeping(&self) -> bool {
+        self.sleeping
+    }
+
+    /// Adds delta_time to the time the body has been slow enough to sleep,
+    /// or restarts it if the body is faster. Returns the time.
+    pub fn update_sleep_time(&mut self, delta_time: Float) -> Float {
+        if self.linear_velocity.magnitude() < SLEEP_LINEAR_VELOCITY
+            && self.angular_velocity.magnitude() < SLEEP_ANGULAR_VELOCITY
+        {
+            self.sleep_time += delta_time;
+        } else {
+            self.sleep_time = 0.0;
+        }
+        self.sleep_time
+    }
+
+    /// Moving a sleeping body wakes it, without touching the timer of an awake one
+    /// since the solver sets the velocities every step.
+    fn wake_if_sleeping(&mut self) {
+        if self.sleeping {
+            self.wake();
+        }
+    }
+
     fn clear_force(&mut self) {
         self.force_accum = Vector3::zeros();
         self.torque_accum = Vector3::zeros();
     }
 
     pub fn get_inv_inertia_tensor_local(&self) -> Matrix3 {
-        self.inv_mass * self.shape.get_inv_inertia_tensor()
+        self.get_inv_mass() * self.shape.get_inv_inertia_tensor()
     }
     pub fn get_inv_inertia_tensor_world(&self) -> Matrix3 {
         let rotation = self.position.rotation.to_rotation_matrix().into_inner();
-        rotation * self.inv_mass * self.shape.get_inv_inertia_tensor() * rotation.transpose()
+        rotation * self.get_inv_mass() * self.shape.get_inv_inertia_tensor() * rotation.transpose()
     }
 
     pub fn get_center_of_mass_local(&self) -> Point3 {
@@ -154,35 +297,114 @@ impl RigidBody {
         &self.linear_velocity
     }
     pub fn set_linear_velocity(&mut self, v: &Vector3) {
+        self.wake_if_sleeping();
         self.linear_velocity = *v;
     }
     pub fn get_angular_velocity(&self) -> &Vector3 {
         &self.angular_velocity
     }
     pub fn set_angular_velocity(&mut self, v: &Vector3) {
+        self.wake_if_sleeping();
         self.angular_velocity = *v;
     }
     pub fn get_position(&self) -> &Isometry3 {
         &self.position
     }
     pub fn set_position(&mut self, iso: &Isometry3) {
+        self.wake_if_sleeping();
         self.moved = true;
         self.position = *iso;
+        self.previous_position = *iso;
+    }
+    pub fn get_previous_position(&self) -> &Isometry3 {
+        &self.previous_position
+    }
+    pub fn set_previous_position(&mut self, iso: &Isometry3) {
+        self.previous_position = *iso;
+    }
+    /// Returns the position blended from the previous one by alpha in [0, 1].
+    pub fn get_interpolated_position(&self, alpha: Float) -> Isometry3 {
+        self.previous_position.lerp_slerp(&self.position, alpha)
     }
     pub fn append_translation(&mut self, translation: &Translation3) {
         self.position.append_translation_mut(translation);
     }
+    /// Returns 0 unless the body is dynamic.
     pub fn get_inv_mass(&self) -> Float {
-        self.inv_mass
+        match self.body_type {
+            BodyType::Dynamic => self.inv_mass,
+            BodyType::Static | BodyType::Kinematic => 0.0,
+        }
     }
+    /// Like `new`, an inv_mass of 0 makes the body static and any other makes it dynamic.
+    /// Kinematic bodies stay kinematic.
     pub fn set_inv_mass(&mut self, inv_mass: Float) {
+        assert!(inv_mass >= 0.0, "inv_mass must not be negative");
         self.inv_mass = inv_mass;
+        if !self.is_kinematic() {
+            self.set_body_type(if inv_mass == 0.0 {
+                BodyType::Static
+            } else {
+                BodyType::Dynamic
+            });
+        }
+    }
+    pub fn get_body_type(&self) -> BodyType {
+        self.body_type
+    }
+    /// A dynamic body needs an inv_mass other than 0.
+    pub fn set_body_type(&mut self, body_type: BodyType) {
+        assert!(
+            body_type != BodyType::Dynamic || self.inv_mass > 0.0,
+            "dynamic bodies need an inv_mass greater than 0"
+        );
+        if body_type != BodyType::Dynamic {
+            self.sleeping = false;
+            self.clear_force();
+        }
+        if body_type == BodyType::Static {
+            self.linear_velocity = Vector3::zeros();
+            self.angular_velocity = Vector3::zeros();
+        }
+        self.body_type = body_type;
+    }
+    pub fn is_dynamic(&self) -> bool {
+        self.body_type == BodyType::Dynamic
+    }
+    pub fn is_kinematic(&self) -> bool {
+        self.body_type == BodyType::Kinematic
+    }
+    /// Returns true if the body is kinematic and has a velocity.
+    pub fn is_kinematic_moving(&self) -> bool {
+        self.is_kinematic()
+            && (self.linear_velocity != Vector3::zeros()
+                || self.angular_velocity != Vector3::zeros())
+    }
+    pub fn get_kinematic_target(&self) -> Option<&Isometry3> {
+        self.kinematic_target.as_ref()
+    }
+    /// Makes a kinematic body move to the position in each step, until the target is cleared.
+    /// Its velocities are set to reach it.
+    pub fn set_kinematic_target(&mut self, target: &Isometry3) {
+        self.kinematic_target = Some(*target);
+    }
+    /// Makes a kinematic body move with its velocities again, starting from rest.
+    pub fn clear_kinematic_target(&mut self) {
+        self.kinematic_target = None;
+        self.linear_velocity = Vector3::zeros();
+        self.angular_velocity = Vector3::zeros();
+    }
+    pub fn get_material(&self) -> &PhysicsMaterial {
+        &self.material
+    }
+    pub fn set_material(&mut self, material: &PhysicsMaterial) {
+        self.material = *material;
     }
     pub fn get_elasticity(&self) -> Float {
-        self.elasticity
+        self.material.restitution
     }
     pub fn set_elasticity(&mut self, elasticity: Float) {
-        self.elasticity = elasticity;
+        self.material.restitution = elasticity;
     }
 
     pub fn get_damping(&self) -> Float {
@@ -201,12 +423,53 @@ impl RigidBody {
         self.anguar_damping = damping
     }
 
+    /// Returns the dynamic friction of the material.
     pub fn get_friction(&self) -> Float {
-        self.friction
+        self.material.dynamic_friction
     }
 
+    /// Sets both the static and the dynamic friction of the material.
     pub fn set_friction(&mut self, friction: Float) {
-        self.friction = friction
+        self.material.static_friction = friction;
+        self.material.dynamic_friction = friction;
+    }
+
+    pub fn get_ccd(&self) -> bool {
+        self.ccd
+    }
+
+    pub fn set_ccd(&mut self, ccd: bool) {
+        self.ccd = ccd
+    }
+
+    pub fn is_sensor(&self) -> bool {
+        self.sensor
+    }
+
+    pub fn set_sensor(&mut self, sensor: bool) {
+        self.sensor = sensor
+    }
+
+    pub fn get_collision_groups(&self) -> u32 {
+        self.collision_groups
+    }
+
+    pub fn set_collision_groups(&mut self, groups: u32) {
+        self.collision_groups = groups
+    }
+
+    pub fn get_collision_mask(&self) -> u32 {
+        self.collision_mask
+    }
+
+    pub fn set_collision_mask(&mut self, mask: u32) {
+        self.collision_mask = mask
+    }
+
+    /// Returns true if each body is in a layer the other collides with.
+    pub fn can_collide_with(&self, other: &Self) -> bool {
+        self.collision_groups & other.collision_mask != 0
+            && other.collision_groups & self.collision_mask != 0
     }
 
     pub fn world_to_local_vector(&self, v: &Vector3) -> Vector3 {
@@ -226,6 +489,15 @@ impl RigidBody {
         self.shape.contact(&other.shape, &pos)
     }
 
+    pub fn contact_manifold<S: Storage<Contact>>(&self, other: &Self, manifold: &mut S) {
+        let pos = self.position.inverse() * other.position;
+        self.shape.contact_manifold(&other.shape, &pos, manifold);
+    }
+
+    pub fn get_shape(&self) -> &ShapeType {
+        &self.shape
+    }
+
     pub fn get_aabb(&self) -> AABB {
         self.shape.build_aabb(&self.position)
     }
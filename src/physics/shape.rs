@@ -1,6 +1,16 @@
+pub mod capsule;
+pub mod compound;
+pub mod convex_hull;
 pub mod cube;
+pub mod height_field;
+pub mod plane;
 pub mod sphere;
-pub use self::{cube::*, sphere::*};
+pub mod tri_mesh;
+pub mod triangle;
+pub use self::{
+    capsule::*, compound::*, convex_hull::*, cube::*, height_field::*, plane::*, sphere::*,
+    tri_mesh::*, triangle::*,
+};
 
 use crate::math::*;
 use crate::physics::*;
@@ -9,6 +19,12 @@ use crate::physics::*;
 pub enum ShapeType {
     Sphere(Sphere),
     Cube(Cube),
+    ConvexHull(ConvexHull),
+    Capsule(Capsule),
+    TriMesh(TriMesh),
+    HeightField(HeightField),
+    Compound(Compound),
+    Plane(Plane),
 }
 
 impl ShapeType {
@@ -35,12 +51,163 @@ impl ShapeType {
                 contact
             }
             (Self::Cube(c1), Self::Cube(c2)) => contact_cube_cube(c1, c2, pos12),
+            (Self::Capsule(c), Self::Sphere(s)) => contact_capsule_sphere(c, s, pos12),
+            (Self::Sphere(s), Self::Capsule(c)) => {
+                contact_capsule_sphere(c, s, &pos12.inverse()).map(|c| c.flip())
+            }
+            (Self::Cube(c1), Self::Capsule(c2)) => contact_cube_capsule(c1, c2, pos12),
+            (Self::Capsule(c1), Self::Cube(c2)) => {
+                contact_cube_capsule(c2, c1, &pos12.inverse()).map(|c| c.flip())
+            }
+            (Self::Compound(c), _) => contact_compound_shape(c, other, pos12),
+            (_, Self::Compound(c)) => {
+                contact_compound_shape(c, self, &pos12.inverse()).map(|c| c.flip())
+            }
+            // planes, meshes and height fields are static, so they never contact each other.
+            (
+                Self::Plane(_) | Self::TriMesh(_) | Self::HeightField(_),
+                Self::Plane(_) | Self::TriMesh(_) | Self::HeightField(_),
+            ) => None,
+            (Self::Plane(p), _) => contact_plane_convex(p, other.as_shape(), pos12),
+            (_, Self::Plane(p)) => {
+                contact_plane_convex(p, self.as_shape(), &pos12.inverse()).map(|c| c.flip())
+            }
+            (Self::TriMesh(m), _) => contact_tri_mesh_shape(m, other, pos12),
+            (_, Self::TriMesh(m)) => {
+                contact_tri_mesh_shape(m, self, &pos12.inverse()).map(|c| c.flip())
+            }
+            (Self::HeightField(h), _) => contact_height_field_shape(h, other, pos12),
+            (_, Self::HeightField(h)) => {
+                contact_height_field_shape(h, self, &pos12.inverse()).map(|c| c.flip())
+            }
+            // every other pair of convex shapes goes through GJK and EPA.
+            _ => contact_convex_convex(self.as_shape(), other.as_shape(), pos12),
+        }
+    }
+
+    /// Stores the contact points of the shapes into manifold.
+    /// Boxes touching boxes or planes get several points, compounds get the points of each
+    /// child touching the other shape, other pairs get the single deepest point.
+    pub fn contact_manifold<S: Storage<Contact>>(
+        &self,
+        other: &Self,
+        pos12: &Isometry3,
+        manifold: &mut S,
+    ) {
+        match (self, other) {
+            (Self::Compound(c), _) => contact_manifold_compound_shape(c, other, pos12, manifold),
+            (_, Self::Compound(c)) => {
+                contact_manifold_compound_shape(c, self, &pos12.inverse(), &mut Flipped(manifold))
+            }
+            (Self::Cube(c1), Self::Cube(c2)) => contact_manifold_cube_cube(c1, c2, pos12, manifold),
+            (Self::Plane(p), Self::Cube(c)) => contact_manifold_plane_cube(p, c, pos12, manifold),
+            (Self::Cube(c), Self::Plane(p)) => {
+                contact_manifold_plane_cube(p, c, &pos12.inverse(), &mut Flipped(manifold))
+            }
+            _ => {
+                if let Some(contact) = self.contact(other, pos12) {
+                    manifold.store(contact);
+                }
+            }
+        }
+    }
+
+    /// Returns the closest points of the shapes as a contact with positive separation,
+    /// or None if they're farther apart than max_distance.
+    /// Intersecting shapes get a penetration contact.
+    pub fn closest_points(
+        &self,
+        other: &Self,
+        pos12: &Isometry3,
+        max_distance: Float,
+    ) -> Option<Contact> {
+        match (self, other) {
+            (Self::Compound(c), _) => closest_points_compound_shape(c, other, pos12, max_distance),
+            (_, Self::Compound(c)) => {
+                closest_points_compound_shape(c, self, &pos12.inverse(), max_distance)
+                    .map(|c| c.flip())
+            }
+            (
+                Self::Plane(_) | Self::TriMesh(_) | Self::HeightField(_),
+                Self::Plane(_) | Self::TriMesh(_) | Self::HeightField(_),
+            ) => None,
+            (Self::Plane(p), _) => {
+                closest_points_plane_convex(p, other.as_shape(), pos12, max_distance)
+            }
+            (_, Self::Plane(p)) => {
+                closest_points_plane_convex(p, self.as_shape(), &pos12.inverse(), max_distance)
+                    .map(|c| c.flip())
+            }
+            (Self::TriMesh(m), _) => closest_points_tri_mesh_shape(m, other, pos12, max_distance),
+            (_, Self::TriMesh(m)) => {
+                closest_points_tri_mesh_shape(m, self, &pos12.inverse(), max_distance)
+                    .map(|c| c.flip())
+            }
+            (Self::HeightField(h), _) => {
+                closest_points_height_field_shape(h, other, pos12, max_distance)
+            }
+            (_, Self::HeightField(h)) => {
+                closest_points_height_field_shape(h, self, &pos12.inverse(), max_distance)
+                    .map(|c| c.flip())
+            }
             _ => {
-                unimplemented!()
+                closest_points_convex_convex(self.as_shape(), other.as_shape(), pos12, max_distance)
             }
         }
     }
 
+    /// Returns true if the shapes overlap or touch.
+    /// TriMesh, HeightField and Plane never intersect each other.
+    pub fn intersect(&self, other: &Self, pos12: &Isometry3) -> bool {
+        match (self, other) {
+            (Self::Sphere(s1), Self::Sphere(s2)) => intersect_sphere_sphere(s1, s2, pos12),
+            _ => self.closest_points(other, pos12, 0.0).is_some(),
+        }
+    }
+
+    /// Returns true if the point in the shape's local coordinates is inside it.
+    /// TriMesh and HeightField are surfaces, so they don't contain any point.
+    pub fn contains_point(&self, point: &Point3) -> bool {
+        match self {
+            Self::Sphere(s) => contains_point_sphere(s, point),
+            Self::Cube(c) => contains_point_cube(c, point),
+            Self::ConvexHull(h) => contains_point_convex_hull(h, point),
+            Self::Capsule(c) => contains_point_capsule(c, point),
+            Self::TriMesh(_) | Self::HeightField(_) => false,
+            Self::Compound(c) => contains_point_compound(c, point),
+            Self::Plane(p) => contains_point_plane(p, point),
+        }
+    }
+
+    /// Casts a ray in the shape's local coordinates.
+    /// Returns the distance to the first hit within max_toi and the normal there.
+    pub fn cast_ray(
+        &self,
+        origin: &Point3,
+        dir: &UnitVector3,
+        max_toi: Float,
+    ) -> Option<(Float, UnitVector3)> {
+        match self {
+            Self::Sphere(s) => cast_ray_sphere(s, origin, dir, max_toi),
+            Self::Cube(c) => cast_ray_cube(c, origin, dir, max_toi),
+            Self::ConvexHull(h) => cast_ray_convex_hull(h, origin, dir, max_toi),
+            Self::Capsule(c) => cast_ray_capsule(c, origin, dir, max_toi),
+            Self::TriMesh(m) => cast_ray_tri_mesh(m, origin, dir, max_toi),
+            Self::HeightField(h) => cast_ray_height_field(h, origin, dir, max_toi),
+            Self::Compound(c) => cast_ray_compound(c, origin, dir, max_toi),
+            Self::Plane(p) => cast_ray_plane(p, origin, dir, max_toi),
+        }
+    }
+
+    /// Returns true if the shape can only be used on static bodies.
+    pub fn is_static_only(&self) -> bool {
+        match self {
+            Self::TriMesh(_) | Self::HeightField(_) | Self::Plane(_) => true,
+            Self::Compound(c) => c.children().iter().any(|c| c.shape.is_static_only()),
+            _ => false,
+        }
+    }
+
     pub fn build_aabb(&self, pos: &Isometry3) -> AABB {
         self.as_shape().build_aabb(pos)
     }
@@ -52,6 +219,12 @@ impl ShapeType {
         match self {
             Self::Sphere(s) => s,
             Self::Cube(c) => c,
+            Self::ConvexHull(h) => h,
+            Self::Capsule(c) => c,
+            Self::TriMesh(m) => m,
+            Self::HeightField(h) => h,
+            Self::Compound(c) => c,
+            Self::Plane(p) => p,
         }
     }
 }
@@ -0,0 +1,94 @@
+use crate::math::*;
+use crate::physics::*;
+
+/// A capsule whose segment lies along the local y axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Capsule {
+    pub radius: Float,
+    pub half_height: Float,
+}
+
+impl Capsule {
+    pub fn new(radius: Float, half_height: Float) -> Self {
+        Self {
+            radius,
+            half_height,
+        }
+    }
+
+    /// Returns the end points of the inner segment.
+    pub fn segment(&self) -> (Point3, Point3) {
+        (
+            Point3::new(0.0, -self.half_height, 0.0),
+            Point3::new(0.0, self.half_height, 0.0),
+        )
+    }
+
+    pub fn segment_world(&self, position: &Isometry3) -> (Point3, Point3) {
+        let (a, b) = self.segment();
+        (position.transform_point(&a), position.transform_point(&b))
+    }
+}
+
+impl Shape for Capsule {
+    fn supporting_point(&self, dir: &UnitVector3, bias: Float) -> Point3 {
+        let y = if dir.y > 0.0 {
+            self.half_height
+        } else {
+            -self.half_height
+        };
+        Point3::new(0.0, y, 0.0) + (self.radius + bias) * dir.into_inner()
+    }
+
+    fn get_center_of_mass(&self) -> Point3 {
+        Point3::origin()
+    }
+
+    fn get_inertia_tensor(&self) -> Matrix3 {
+        let r = self.radius;
+        let h = self.half_height;
+        // mass ratio of the cylinder and the two hemispheres.
+        let cylinder_volume = 2.0 * h * r * r;
+        let sphere_volume = 4.0 / 3.0 * r * r * r;
+        let cylinder_mass = cylinder_volume / (cylinder_volume + sphere_volume);
+        let sphere_mass = sphere_volume / (cylinder_volume + sphere_volume);
+
+        let axial = cylinder_mass * r * r / 2.0 + sphere_mass * 2.0 * r * r / 5.0;
+        let perpendicular = cylinder_mass * (r * r / 4.0 + h * h / 3.0)
+            + sphere_mass * (2.0 * r * r / 5.0 + h * h + 3.0 * h * r / 4.0);
+        let mut tensor = Matrix3::zeros();
+        tensor[(0, 0)] = perpendicular;
+        tensor[(1, 1)] = axial;
+        tensor[(2, 2)] = perpendicular;
+        tensor
+    }
+
+    fn build_aabb(&self, pos: &Isometry3) -> AABB {
+        let (a, b) = self.segment_world(pos);
+        let radii = Vector3::new(self.radius, self.radius, self.radius);
+        AABB::new(a, radii).merge(&AABB::new(b, radii))
+    }
+
+    fn build_bounding_sphere(&self, pos: &Isometry3) -> BoundingSphere {
+        let center = pos.translation.vector.into();
+        let radius = self.radius + self.half_height;
+        BoundingSphere { center, radius }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_capsule_inertia() {
+        let capsule = Capsule::new(1.0, 0.0);
+        let sphere = Sphere::new(1.0);
+        let diff = capsule.get_inertia_tensor() - sphere.get_inertia_tensor();
+        assert!(diff.abs().max() < 1.0e-5);
+
+        let capsule = Capsule::new(0.5, 1.0);
+        let tensor = capsule.get_inertia_tensor();
+        assert!(tensor[(0, 0)] > tensor[(1, 1)]);
+        assert_eq!(tensor[(0, 0)], tensor[(2, 2)]);
+    }
+}
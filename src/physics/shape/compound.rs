@@ -0,0 +1,137 @@
+use crate::math::*;
+use crate::physics::*;
+
+#[derive(Debug, Clone)]
+pub struct CompoundChild {
+    /// Position relative to the compound.
+    pub position: Isometry3,
+    pub shape: ShapeType,
+    /// Mass relative to the other children.
+    pub mass: Float,
+}
+
+impl CompoundChild {
+    pub fn new(position: Isometry3, shape: ShapeType, mass: Float) -> Self {
+        Self {
+            position,
+            shape,
+            mass,
+        }
+    }
+}
+
+/// A shape made of several child shapes with local offsets.
+#[derive(Debug, Clone)]
+pub struct Compound {
+    children: Vec<CompoundChild>,
+    center_of_mass: Point3,
+    inertia_tensor: Matrix3,
+}
+
+impl Compound {
+    pub fn new(children: Vec<CompoundChild>) -> Self {
+        assert!(!children.is_empty(), "Compound needs at least one child");
+        let total_mass = children.iter().map(|c| c.mass).sum::<Float>();
+        let center_of_mass: Point3 = (children
+            .iter()
+            .map(|c| {
+                c.position
+                    .transform_point(&c.shape.get_center_of_mass())
+                    .coords
+                    * c.mass
+            })
+            .sum::<Vector3>()
+            / total_mass)
+            .into();
+
+        // rotate each child's tensor and move it to the center of mass with the parallel axis theorem.
+        let mut inertia_tensor = Matrix3::zeros();
+        for child in &children {
+            let rotation = child.position.rotation.to_rotation_matrix().into_inner();
+            let local = rotation * child.shape.get_inertia_tensor() * rotation.transpose();
+            let d = child
+                .position
+                .transform_point(&child.shape.get_center_of_mass())
+                - center_of_mass;
+            let offset = Matrix3::identity() * d.magnitude_squared() - d * d.transpose();
+            inertia_tensor += child.mass * (local + offset);
+        }
+        inertia_tensor /= total_mass;
+
+        Self {
+            children,
+            center_of_mass,
+            inertia_tensor,
+        }
+    }
+
+    pub fn children(&self) -> &[CompoundChild] {
+        &self.children
+    }
+}
+
+impl Shape for Compound {
+    fn supporting_point(&self, dir: &UnitVector3, bias: Float) -> Point3 {
+        self.children
+            .iter()
+            .map(|c| {
+                let local_dir = c.position.inverse_transform_unit_vector(dir);
+                c.shape
+                    .as_shape()
+                    .supporting_point_world(&local_dir, bias, &c.position)
+            })
+            .max_by(|p1, p2| {
+                let d1 = p1.coords.dot(dir);
+                let d2 = p2.coords.dot(dir);
+                d1.partial_cmp(&d2).unwrap()
+            })
+            .unwrap()
+    }
+
+    fn get_center_of_mass(&self) -> Point3 {
+        self.center_of_mass
+    }
+
+    fn get_inertia_tensor(&self) -> Matrix3 {
+        self.inertia_tensor
+    }
+
+    fn build_aabb(&self, pos: &Isometry3) -> AABB {
+        self.children
+            .iter()
+            .map(|c| c.shape.build_aabb(&(pos * c.position)))
+            .reduce(|a, b| a.merge(&b))
+            .unwrap()
+    }
+
+    fn build_bounding_sphere(&self, pos: &Isometry3) -> BoundingSphere {
+        self.children
+            .iter()
+            .map(|c| c.shape.build_bounding_sphere(&(pos * c.position)))
+            .reduce(|a, b| a.merge(&b))
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_compound_mass_properties() {
+        // two unit cubes side by side make a 2x1x1 box.
+        let cube = ShapeType::Cube(Cube::new(Vector3::new(0.5, 0.5, 0.5)));
+        let compound = Compound::new(vec![
+            CompoundChild::new(Isometry3::translation(0.5, 0.0, 0.0), cube.clone(), 1.0),
+            CompoundChild::new(Isometry3::translation(1.5, 0.0, 0.0), cube, 1.0),
+        ]);
+        assert!((compound.get_center_of_mass() - Point3::new(1.0, 0.0, 0.0)).magnitude() < 1.0e-5);
+        let expected = Cube::new(Vector3::new(1.0, 0.5, 0.5)).get_inertia_tensor();
+        assert!((compound.get_inertia_tensor() - expected).abs().max() < 1.0e-5);
+
+        let aabb = compound.build_aabb(&Isometry3::identity());
+        assert_eq!(
+            aabb,
+            AABB::new(Point3::new(1.0, 0.0, 0.0), Vector3::new(1.0, 0.5, 0.5))
+        );
+    }
+}
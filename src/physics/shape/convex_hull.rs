@@ -0,0 +1,336 @@
+use crate::math::*;
+use crate::physics::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvexHull {
+    vertices: Vec<Point3>,
+    faces: Vec<[usize; 3]>,
+    center_of_mass: Point3,
+    inertia_tensor: Matrix3,
+}
+
+impl ConvexHull {
+    /// Builds the convex hull of a point cloud.
+    /// Returns None if the points don't span a volume.
+    pub fn new(points: &[Point3]) -> Option<Self> {
+        let (vertices, faces) = quickhull(points)?;
+        let (center_of_mass, inertia_tensor) = mass_properties(&vertices, &faces)?;
+        Some(Self {
+            vertices,
+            faces,
+            center_of_mass,
+            inertia_tensor,
+        })
+    }
+
+    pub fn vertices(&self) -> &[Point3] {
+        &self.vertices
+    }
+
+    /// Triangles of the hull. They are counter clockwise when seen from outside.
+    pub fn faces(&self) -> &[[usize; 3]] {
+        &self.faces
+    }
+
+    pub fn vertices_world(&self, position: &Isometry3) -> Vec<Point3> {
+        self.vertices
+            .iter()
+            .map(|v| position.transform_point(v))
+            .collect()
+    }
+}
+
+impl Shape for ConvexHull {
+    fn supporting_point(&self, dir: &UnitVector3, bias: Float) -> Point3 {
+        let mut best = self.vertices[0];
+        let mut best_dot = best.coords.dot(dir);
+        for v in &self.vertices[1..] {
+            let dot = v.coords.dot(dir);
+            if dot > best_dot {
+                best = *v;
+                best_dot = dot;
+            }
+        }
+        best + dir.into_inner() * bias
+    }
+
+    fn get_center_of_mass(&self) -> Point3 {
+        self.center_of_mass
+    }
+
+    fn get_inertia_tensor(&self) -> Matrix3 {
+        self.inertia_tensor
+    }
+
+    fn build_aabb(&self, pos: &Isometry3) -> AABB {
+        let vertices = self.vertices_world(pos);
+        let mut aabb = AABB::new(vertices[0], Vector3::zeros());
+        for v in &vertices[1..] {
+            aabb.expand_mut(v);
+        }
+        aabb
+    }
+
+    fn build_bounding_sphere(&self, pos: &Isometry3) -> BoundingSphere {
+        let radius = self
+            .vertices
+            .iter()
+            .map(|v| (v - self.center_of_mass).magnitude())
+            .fold(0.0, Float::max);
+        let center = pos.transform_point(&self.center_of_mass);
+        BoundingSphere { center, radius }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct HullFace {
+    indices: [usize; 3],
+    normal: Vector3,
+    offset: Float,
+    outside: Vec<usize>,
+}
+
+impl HullFace {
+    fn new(points: &[Point3], indices: [usize; 3]) -> Self {
+        let [a, b, c] = indices.map(|i| points[i]);
+        let normal = (b - a).cross(&(c - a)).normalize();
+        let offset = normal.dot(&a.coords);
+        Self {
+            indices,
+            normal,
+            offset,
+            outside: Vec::new(),
+        }
+    }
+
+    fn distance(&self, p: &Point3) -> Float {
+        self.normal.dot(&p.coords) - self.offset
+    }
+}
+
+/// Computes the convex hull of the points with quickhull.
+/// Returns the hull vertices and counter clockwise triangles indexing them.
+pub fn quickhull(points: &[Point3]) -> Option<(Vec<Point3>, Vec<[usize; 3]>)> {
+    if points.len() < 4 {
+        return None;
+    }
+    let mut aabb = AABB::new(points[0], Vector3::zeros());
+    for p in points {
+        aabb.expand_mut(p);
+    }
+    let epsilon = aabb.radii.max() * 1.0e-5;
+    if epsilon <= 0.0 {
+        return None;
+    }
+
+    // initial tetrahedron from extreme points.
+    let mut extremes = [0; 6];
+    for (i, p) in points.iter().enumerate() {
+        for axis in 0..3 {
+            if p[axis] < points[extremes[axis * 2]][axis] {
+                extremes[axis * 2] = i;
+            }
+            if p[axis] > points[extremes[axis * 2 + 1]][axis] {
+                extremes[axis * 2 + 1] = i;
+            }
+        }
+    }
+    let (i0, i1) = (0..3)
+        .map(|axis| (extremes[axis * 2], extremes[axis * 2 + 1]))
+        .max_by(|(a1, b1), (a2, b2)| {
+            let d1 = (points[*b1] - points[*a1]).magnitude_squared();
+            let d2 = (points[*b2] - points[*a2]).magnitude_squared();
+            d1.partial_cmp(&d2).unwrap()
+        })?;
+    let line = (points[i1] - points[i0]).normalize();
+    let (i2, line_distance) = farthest(points, |p| (p - points[i0]).cross(&line).magnitude())?;
+    if line_distance <= epsilon {
+        return None;
+    }
+    let plane = (points[i1] - points[i0])
+        .cross(&(points[i2] - points[i0]))
+        .normalize();
+    let (i3, plane_distance) = farthest(points, |p| (p - points[i0]).dot(&plane).abs())?;
+    if plane_distance <= epsilon {
+        return None;
+    }
+
+    let mut faces = Vec::new();
+    let inner: Point3 =
+        ((points[i0].coords + points[i1].coords + points[i2].coords + points[i3].coords) * 0.25)
+            .into();
+    for indices in [[i0, i1, i2], [i0, i2, i3], [i0, i3, i1], [i1, i3, i2]] {
+        let face = HullFace::new(points, indices);
+        if face.distance(&inner) > 0.0 {
+            faces.push(HullFace::new(points, [indices[0], indices[2], indices[1]]));
+        } else {
+            faces.push(face);
+        }
+    }
+    let candidates = (0..points.len()).filter(|i| ![i0, i1, i2, i3].contains(i));
+    assign_outside(points, &mut faces, candidates, epsilon);
+
+    while let Some(face_index) = faces.iter().position(|f| !f.outside.is_empty()) {
+        let face = &faces[face_index];
+        let eye = *face
+            .outside
+            .iter()
+            .max_by(|a, b| {
+                let da = face.distance(&points[**a]);
+                let db = face.distance(&points[**b]);
+                da.partial_cmp(&db).unwrap()
+            })
+            .unwrap();
+        let eye_point = points[eye];
+
+        // remove faces visible from the eye point and collect the horizon.
+        let mut horizon: Vec<(usize, usize)> = Vec::new();
+        let mut orphans = Vec::new();
+        faces.retain(|face| {
+            let visible = face.distance(&eye_point) > epsilon;
+            if visible {
+                for k in 0..3 {
+                    let edge = (face.indices[k], face.indices[(k + 1) % 3]);
+                    match horizon.iter().position(|e| *e == (edge.1, edge.0)) {
+                        Some(i) => {
+                            horizon.swap_remove(i);
+                        }
+                        None => horizon.push(edge),
+                    }
+                }
+                orphans.extend(face.outside.iter().filter(|i| **i != eye));
+            }
+            !visible
+        });
+
+        let first_new = faces.len();
+        for (a, b) in horizon {
+            faces.push(HullFace::new(points, [a, b, eye]));
+        }
+        assign_outside(
+            points,
+            &mut faces[first_new..],
+            orphans.into_iter(),
+            epsilon,
+        );
+    }
+
+    // compact vertices used by the hull.
+    let mut remap = vec![usize::MAX; points.len()];
+    let mut vertices = Vec::new();
+    let faces = faces
+        .iter()
+        .map(|face| {
+            face.indices.map(|i| {
+                if remap[i] == usize::MAX {
+                    remap[i] = vertices.len();
+                    vertices.push(points[i]);
+                }
+                remap[i]
+            })
+        })
+        .collect();
+    Some((vertices, faces))
+}
+
+fn farthest<F: Fn(&Point3) -> Float>(points: &[Point3], distance: F) -> Option<(usize, Float)> {
+    points
+        .iter()
+        .map(distance)
+        .enumerate()
+        .max_by(|(_, d1), (_, d2)| d1.partial_cmp(d2).unwrap())
+}
+
+fn assign_outside<I: Iterator<Item = usize>>(
+    points: &[Point3],
+    faces: &mut [HullFace],
+    candidates: I,
+    epsilon: Float,
+) {
+    for i in candidates {
+        if let Some(face) = faces
+            .iter_mut()
+            .find(|face| face.distance(&points[i]) > epsilon)
+        {
+            face.outside.push(i);
+        }
+    }
+}
+
+/// Returns the center of mass and the inertia tensor per unit mass of a closed triangle mesh,
+/// assuming uniform density.
+pub fn mass_properties(vertices: &[Point3], faces: &[[usize; 3]]) -> Option<(Point3, Matrix3)> {
+    let reference = vertices
+        .iter()
+        .fold(Vector3::zeros(), |acc, v| acc + v.coords)
+        / vertices.len() as Float;
+    let canonical = Matrix3::new(2.0, 1.0, 1.0, 1.0, 2.0, 1.0, 1.0, 1.0, 2.0) / 120.0;
+
+    let mut volume = 0.0;
+    let mut weighted_center = Vector3::zeros();
+    let mut covariance = Matrix3::zeros();
+    // sum up tetrahedra made of each face and the reference point.
+    for face in faces {
+        let [a, b, c] = face.map(|i| vertices[i].coords - reference);
+        let det = a.dot(&b.cross(&c));
+        volume += det / 6.0;
+        weighted_center += det / 6.0 * (a + b + c) * 0.25;
+        let m = Matrix3::from_columns(&[a, b, c]);
+        covariance += det * m * canonical * m.transpose();
+    }
+    if volume <= 0.0 {
+        return None;
+    }
+
+    let center = weighted_center / volume;
+    let covariance = covariance - volume * center * center.transpose();
+    let inertia = Matrix3::identity() * covariance.trace() - covariance;
+    Some(((reference + center).into(), inertia / volume))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_convex_hull_cube() {
+        let cube = Cube::new(Vector3::new(1.0, 2.0, 3.0));
+        let mut points = cube.corners().to_vec();
+        points.push(Point3::new(0.1, 0.5, -0.3));
+        points.push(Point3::new(-0.9, 1.0, 2.0));
+        points.push(Point3::new(1.0, 0.0, 0.0));
+        let hull = ConvexHull::new(&points).unwrap();
+
+        assert_eq!(hull.vertices().len(), 8);
+        assert_eq!(hull.faces().len(), 12);
+        assert!(hull.get_center_of_mass().coords.magnitude() < 1.0e-4);
+        let diff = hull.get_inertia_tensor() - cube.get_inertia_tensor();
+        assert!(diff.abs().max() < 1.0e-4);
+
+        let dir = UnitVector3::new_normalize(Vector3::new(1.0, -1.0, 1.0));
+        assert_eq!(
+            hull.supporting_point(&dir, 0.0),
+            cube.supporting_point(&dir, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_convex_hull_offset() {
+        let cube = Cube::new(Vector3::new(0.5, 0.5, 0.5));
+        let points = cube
+            .corners_world(&Isometry3::translation(2.0, 0.0, 1.0))
+            .to_vec();
+        let hull = ConvexHull::new(&points).unwrap();
+        assert!((hull.get_center_of_mass() - Point3::new(2.0, 0.0, 1.0)).magnitude() < 1.0e-4);
+        let diff = hull.get_inertia_tensor() - cube.get_inertia_tensor();
+        assert!(diff.abs().max() < 1.0e-4);
+
+        let planar = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 1.0),
+        ];
+        assert_eq!(ConvexHull::new(&planar), None);
+    }
+}
@@ -0,0 +1,167 @@
+use crate::math::*;
+use crate::physics::*;
+
+/// A grid of heights on the local xz plane, centered on the origin. It's meant for static terrain.
+/// Heights are stored row by row, rows go along z and columns go along x.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeightField {
+    heights: Vec<Float>,
+    rows: usize,
+    cols: usize,
+    scale: Vector3,
+    aabb: AABB,
+}
+
+impl HeightField {
+    /// scale.x and scale.z are the size of the whole field, and scale.y multiplies every height.
+    pub fn new(heights: Vec<Float>, rows: usize, cols: usize, scale: Vector3) -> Self {
+        assert!(
+            rows >= 2 && cols >= 2,
+            "HeightField needs at least 2x2 heights"
+        );
+        assert_eq!(heights.len(), rows * cols);
+        let (min, max) = heights
+            .iter()
+            .fold((FLOAT_MAX, FLOAT_MIN), |(min, max), h| {
+                (min.min(*h * scale.y), max.max(*h * scale.y))
+            });
+        let aabb = AABB::new_min_max(
+            Point3::new(-scale.x * 0.5, min, -scale.z * 0.5),
+            Point3::new(scale.x * 0.5, max, scale.z * 0.5),
+        );
+        Self {
+            heights,
+            rows,
+            cols,
+            scale,
+            aabb,
+        }
+    }
+
+    /// Builds a height field from the brightness of an image. Black is 0 and white is scale.y.
+    pub fn from_image(image: &image::DynamicImage, scale: Vector3) -> Self {
+        let image = image.to_luma8();
+        let (cols, rows) = (image.width() as usize, image.height() as usize);
+        let heights = image.pixels().map(|p| p.0[0] as Float / 255.0).collect();
+        Self::new(heights, rows, cols, scale)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get_height(&self, row: usize, col: usize) -> Float {
+        self.heights[row * self.cols + col] * self.scale.y
+    }
+
+    fn cell_size(&self) -> (Float, Float) {
+        (
+            self.scale.x / (self.cols - 1) as Float,
+            self.scale.z / (self.rows - 1) as Float,
+        )
+    }
+
+    pub fn vertex(&self, row: usize, col: usize) -> Point3 {
+        let (dx, dz) = self.cell_size();
+        Point3::new(
+            col as Float * dx - self.scale.x * 0.5,
+            self.get_height(row, col),
+            row as Float * dz - self.scale.z * 0.5,
+        )
+    }
+
+    /// Returns the two triangles of a cell. Both face +y.
+    pub fn cell_triangles(&self, row: usize, col: usize) -> [Triangle; 2] {
+        let v00 = self.vertex(row, col);
+        let v01 = self.vertex(row, col + 1);
+        let v10 = self.vertex(row + 1, col);
+        let v11 = self.vertex(row + 1, col + 1);
+        [Triangle::new(v00, v10, v11), Triangle::new(v00, v11, v01)]
+    }
+
+    /// Returns ranges of rows and columns of cells under the bounding box in local coordinates.
+    pub fn cells_in(
+        &self,
+        aabb: &AABB,
+    ) -> Option<(std::ops::Range<usize>, std::ops::Range<usize>)> {
+        if !self.aabb.intersect(aabb) {
+            return None;
+        }
+        let (dx, dz) = self.cell_size();
+        let min = aabb.center - aabb.radii;
+        let max = aabb.center + aabb.radii;
+        let cell = |v: Float, half: Float, size: Float, count: usize| {
+            (((v + half) / size).floor().max(0.0) as usize).min(count - 2)
+        };
+        let col0 = cell(min.x, self.scale.x * 0.5, dx, self.cols);
+        let col1 = cell(max.x, self.scale.x * 0.5, dx, self.cols);
+        let row0 = cell(min.z, self.scale.z * 0.5, dz, self.rows);
+        let row1 = cell(max.z, self.scale.z * 0.5, dz, self.rows);
+        Some((row0..row1 + 1, col0..col1 + 1))
+    }
+
+    pub fn get_local_aabb(&self) -> AABB {
+        self.aabb
+    }
+}
+
+impl Shape for HeightField {
+    fn supporting_point(&self, dir: &UnitVector3, bias: Float) -> Point3 {
+        let mut best = self.vertex(0, 0);
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let v = self.vertex(row, col);
+                if v.coords.dot(dir) > best.coords.dot(dir) {
+                    best = v;
+                }
+            }
+        }
+        best + dir.into_inner() * bias
+    }
+
+    fn get_center_of_mass(&self) -> Point3 {
+        self.aabb.center
+    }
+
+    fn get_inertia_tensor(&self) -> Matrix3 {
+        // approximated by the bounding box. height fields are meant to be static.
+        Cube::new(self.aabb.radii).get_inertia_tensor()
+    }
+
+    fn build_aabb(&self, pos: &Isometry3) -> AABB {
+        let center = self.aabb.center;
+        let local = Isometry3::translation(center.x, center.y, center.z);
+        Cube::new(self.aabb.radii).build_aabb(&(pos * local))
+    }
+
+    fn build_bounding_sphere(&self, pos: &Isometry3) -> BoundingSphere {
+        let center = pos.transform_point(&self.aabb.center);
+        BoundingSphere::new(center, self.aabb.radii.magnitude())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_height_field_from_image() {
+        let image = image::DynamicImage::ImageLuma8(image::GrayImage::from_fn(3, 2, |x, _| {
+            image::Luma([(x * 51) as u8])
+        }));
+        let field = HeightField::from_image(&image, Vector3::new(4.0, 10.0, 2.0));
+        assert_eq!((field.rows(), field.cols()), (2, 3));
+        assert_eq!(field.vertex(0, 0), Point3::new(-2.0, 0.0, -1.0));
+        assert_eq!(field.vertex(1, 2), Point3::new(2.0, 4.0, 1.0));
+        let normal = field.cell_triangles(0, 0)[0].normal().unwrap();
+        assert!(normal.y > 0.0);
+
+        let aabb = AABB::new(Point3::new(1.5, 0.0, 0.0), Vector3::new(0.1, 5.0, 0.1));
+        assert_eq!(field.cells_in(&aabb), Some((0..1, 1..2)));
+        let aabb = AABB::new(Point3::new(5.0, 0.0, 0.0), Vector3::new(0.1, 5.0, 0.1));
+        assert_eq!(field.cells_in(&aabb), None);
+    }
+}
@@ -0,0 +1,57 @@
+use crate::math::*;
+use crate::physics::*;
+
+/// An infinite half space. Points p with normal.dot(p) <= offset are inside.
+/// It's only valid on static bodies and is kept out of the BVH.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: UnitVector3,
+    pub offset: Float,
+}
+
+impl Plane {
+    /// Size used where a finite value is needed, such as bounding volumes.
+    pub const EXTENT: Float = 1.0e5;
+
+    pub fn new(normal: UnitVector3, offset: Float) -> Self {
+        Self { normal, offset }
+    }
+
+    /// Returns signed distance from the plane. Negative value indicates the point is inside.
+    pub fn distance(&self, p: &Point3) -> Float {
+        self.normal.dot(&p.coords) - self.offset
+    }
+
+    pub fn project_point(&self, p: &Point3) -> Point3 {
+        p - self.normal.into_inner() * self.distance(p)
+    }
+}
+
+impl Shape for Plane {
+    fn supporting_point(&self, dir: &UnitVector3, bias: Float) -> Point3 {
+        let normal = self.normal.into_inner();
+        let along = dir.dot(&normal);
+        let tangent = dir.into_inner() - normal * along;
+        let depth = if along < 0.0 { -Self::EXTENT } else { 0.0 };
+        Point3::from(normal * (self.offset + depth))
+            + tangent * Self::EXTENT
+            + dir.into_inner() * bias
+    }
+
+    fn get_center_of_mass(&self) -> Point3 {
+        (self.normal.into_inner() * self.offset).into()
+    }
+
+    fn get_inertia_tensor(&self) -> Matrix3 {
+        Matrix3::identity()
+    }
+
+    fn build_aabb(&self, pos: &Isometry3) -> AABB {
+        let radii = Vector3::new(Self::EXTENT, Self::EXTENT, Self::EXTENT);
+        AABB::new(pos.translation.vector.into(), radii)
+    }
+
+    fn build_bounding_sphere(&self, pos: &Isometry3) -> BoundingSphere {
+        BoundingSphere::new(pos.translation.vector.into(), Self::EXTENT)
+    }
+}
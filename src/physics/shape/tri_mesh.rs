@@ -0,0 +1,98 @@
+use crate::math::*;
+use crate::physics::*;
+
+/// A triangle mesh for static bodies.
+/// Triangles are kept in a BVH so that contacts only test nearby ones.
+#[derive(Debug, Clone)]
+pub struct TriMesh {
+    vertices: Vec<Point3>,
+    triangles: Vec<[u32; 3]>,
+    bvh: BVH<AABB, usize>,
+    aabb: AABB,
+}
+
+impl TriMesh {
+    /// Builds a mesh from vertices and a triangle list, in the same layout as render meshes.
+    /// Panics if there are no vertices, or if an index is out of range.
+    pub fn new(vertices: Vec<Point3>, indices: &[u32]) -> Self {
+        assert!(!vertices.is_empty(), "TriMesh needs at least one vertex");
+        assert_eq!(indices.len() % 3, 0, "TriMesh indices must be triangles");
+        assert!(
+            indices.iter().all(|i| (*i as usize) < vertices.len()),
+            "TriMesh index out of range"
+        );
+        let triangles = indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]])
+            .collect::<Vec<_>>();
+        let mut aabb = AABB::new(vertices[0], Vector3::zeros());
+        for v in &vertices[1..] {
+            aabb.expand_mut(v);
+        }
+        let mut mesh = Self {
+            vertices,
+            triangles,
+            bvh: BVH::new(),
+            aabb,
+        };
+        for i in 0..mesh.triangles.len() {
+            let bounding = mesh.triangle(i).build_aabb(&Isometry3::identity());
+            mesh.bvh.insert(i, bounding);
+        }
+        mesh
+    }
+
+    pub fn vertices(&self) -> &[Point3] {
+        &self.vertices
+    }
+
+    pub fn triangles(&self) -> &[[u32; 3]] {
+        &self.triangles
+    }
+
+    pub fn triangle(&self, i: usize) -> Triangle {
+        let [a, b, c] = self.triangles[i].map(|v| self.vertices[v as usize]);
+        Triangle::new(a, b, c)
+    }
+
+    /// Stores indices of triangles overlapping with the bounding box in local coordinates.
+    pub fn query_triangles<'a, S: Storage<&'a usize>>(&'a self, aabb: &AABB, storage: &mut S) {
+        self.bvh.query(aabb, storage);
+    }
+
+    pub fn get_local_aabb(&self) -> AABB {
+        self.aabb
+    }
+}
+
+impl Shape for TriMesh {
+    fn supporting_point(&self, dir: &UnitVector3, bias: Float) -> Point3 {
+        let mut best = self.vertices[0];
+        for v in &self.vertices[1..] {
+            if v.coords.dot(dir) > best.coords.dot(dir) {
+                best = *v;
+            }
+        }
+        best + dir.into_inner() * bias
+    }
+
+    fn get_center_of_mass(&self) -> Point3 {
+        self.aabb.center
+    }
+
+    fn get_inertia_tensor(&self) -> Matrix3 {
+        // approximated by the bounding box. meshes are meant to be static.
+        Cube::new(self.aabb.radii).get_inertia_tensor()
+    }
+
+    fn build_aabb(&self, pos: &Isometry3) -> AABB {
+        let local =
+            Isometry3::translation(self.aabb.center.x, self.aabb.center.y, self.aabb.center.z);
+        Cube::new(self.aabb.radii).build_aabb(&(pos * local))
+    }
+
+    fn build_bounding_sphere(&self, pos: &Isometry3) -> BoundingSphere {
+        let center = pos.transform_point(&self.aabb.center);
+        BoundingSphere::new(center, self.aabb.radii.magnitude())
+    }
+}
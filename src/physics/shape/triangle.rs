@@ -0,0 +1,107 @@
+use crate::math::*;
+use crate::physics::*;
+
+/// A single triangle. It's used for contacts against triangle meshes and height fields.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Triangle {
+    pub a: Point3,
+    pub b: Point3,
+    pub c: Point3,
+}
+
+impl Triangle {
+    pub fn new(a: Point3, b: Point3, c: Point3) -> Self {
+        Self { a, b, c }
+    }
+
+    /// Returns the normal of the counter clockwise side.
+    pub fn normal(&self) -> Option<UnitVector3> {
+        UnitVector3::try_new((self.b - self.a).cross(&(self.c - self.a)), 1.0e-10)
+    }
+
+    /// Returns the point on the triangle closest to p.
+    pub fn closest_point(&self, p: &Point3) -> Point3 {
+        let (a, b, c) = (self.a, self.b, self.c);
+        let ab = b - a;
+        let ac = c - a;
+        let ap = p - a;
+        let d1 = ab.dot(&ap);
+        let d2 = ac.dot(&ap);
+        if d1 <= 0.0 && d2 <= 0.0 {
+            return a;
+        }
+        let bp = p - b;
+        let d3 = ab.dot(&bp);
+        let d4 = ac.dot(&bp);
+        if d3 >= 0.0 && d4 <= d3 {
+            return b;
+        }
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+            return a + ab * (d1 / (d1 - d3));
+        }
+        let cp = p - c;
+        let d5 = ab.dot(&cp);
+        let d6 = ac.dot(&cp);
+        if d6 >= 0.0 && d5 <= d6 {
+            return c;
+        }
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+            return a + ac * (d2 / (d2 - d6));
+        }
+        let va = d3 * d6 - d5 * d4;
+        if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+            return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+        }
+        let denom = 1.0 / (va + vb + vc);
+        a + ab * (vb * denom) + ac * (vc * denom)
+    }
+}
+
+impl Shape for Triangle {
+    fn supporting_point(&self, dir: &UnitVector3, bias: Float) -> Point3 {
+        let da = self.a.coords.dot(dir);
+        let db = self.b.coords.dot(dir);
+        let dc = self.c.coords.dot(dir);
+        let p = if da >= db && da >= dc {
+            self.a
+        } else if db >= dc {
+            self.b
+        } else {
+            self.c
+        };
+        p + dir.into_inner() * bias
+    }
+
+    fn get_center_of_mass(&self) -> Point3 {
+        ((self.a.coords + self.b.coords + self.c.coords) / 3.0).into()
+    }
+
+    fn get_inertia_tensor(&self) -> Matrix3 {
+        // treat the vertices as point masses.
+        let center = self.get_center_of_mass();
+        let mut covariance = Matrix3::zeros();
+        for p in [self.a, self.b, self.c] {
+            let r = p - center;
+            covariance += r * r.transpose() / 3.0;
+        }
+        Matrix3::identity() * covariance.trace() - covariance
+    }
+
+    fn build_aabb(&self, pos: &Isometry3) -> AABB {
+        let a = pos.transform_point(&self.a);
+        AABB::new(a, Vector3::zeros())
+            .expand(&pos.transform_point(&self.b))
+            .expand(&pos.transform_point(&self.c))
+    }
+
+    fn build_bounding_sphere(&self, pos: &Isometry3) -> BoundingSphere {
+        let center = self.get_center_of_mass();
+        let radius = [self.a, self.b, self.c]
+            .iter()
+            .map(|p| (p - center).magnitude())
+            .fold(0.0, Float::max);
+        BoundingSphere::new(pos.transform_point(&center), radius)
+    }
+}
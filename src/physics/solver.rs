@@ -0,0 +1,612 @@
+use super::*;
+use crate::math::*;
+use std::collections::HashMap;
+
+/// Relative normal speed below which contacts don't bounce, so that resting bodies stay at rest.
+const RESTITUTION_THRESHOLD: Float = 1.0;
+/// Penetration depth allowed without correction. Keeps resting contacts alive between steps.
+const ALLOWED_PENETRATION: Float = 0.005;
+/// Fraction of the penetration corrected in a step.
+const BAUMGARTE: Float = 0.2;
+/// Relative sliding speed under which contacts hold with static friction.
+const STATIC_FRICTION_VELOCITY: Float = 0.1;
+
+/// Velocities of a body copied out of the world while solving.
+#[derive(Debug, Clone, Copy)]
+struct SolverBody {
+    linear_velocity: Vector3,
+    angular_velocity: Vector3,
+    /// Velocities which only push penetrating bodies apart.
+    /// They move the bodies in this step and are thrown away, so they don't add energy.
+    pseudo_linear_velocity: Vector3,
+    pseudo_angular_velocity: Vector3,
+    inv_mass: Float,
+    inv_inertia: Matrix3,
+}
+
+impl SolverBody {
+    fn new(body: &RigidBody) -> Self {
+        Self {
+            linear_velocity: *body.get_linear_velocity(),
+            angular_velocity: *body.get_angular_velocity(),
+            pseudo_linear_velocity: Vector3::zeros(),
+            pseudo_angular_velocity: Vector3::zeros(),
+            inv_mass: body.get_inv_mass(),
+            inv_inertia: body.get_inv_inertia_tensor_world(),
+        }
+    }
+
+    fn velocity_at(&self, r: &Vector3) -> Vector3 {
+        self.linear_velocity + self.angular_velocity.cross(r)
+    }
+
+    fn pseudo_velocity_at(&self, r: &Vector3) -> Vector3 {
+        self.pseudo_linear_velocity + self.pseudo_angular_velocity.cross(r)
+    }
+
+    fn apply_impulse(&mut self, impulse: &Vector3, r: &Vector3) {
+        self.linear_velocity += self.inv_mass * impulse;
+        self.angular_velocity += self.inv_inertia * r.cross(impulse);
+    }
+
+    fn apply_angular_impulse(&mut self, impulse: &Vector3) {
+        self.angular_velocity += self.inv_inertia * impulse;
+    }
+
+    fn apply_pseudo_impulse(&mut self, impulse: &Vector3, r: &Vector3) {
+        self.pseudo_linear_velocity += self.inv_mass * impulse;
+        self.pseudo_angular_velocity += self.inv_inertia * r.cross(impulse);
+    }
+
+    /// Returns the inverse of the effective mass along the direction at r.
+    fn inv_effective_mass(&self, r: &Vector3, dir: &Vector3) -> Float {
+        self.inv_mass + (self.inv_inertia * r.cross(dir)).cross(r).dot(dir)
+    }
+}
+
+/// Identifies a contact across steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct ContactKey {
+    body1: RigidBodyId,
+    body2: RigidBodyId,
+    feature: u32,
+}
+
+/// Accumulated impulses of a contact kept from the previous step.
+/// The friction impulse is in world coordinates, since the tangents change between steps.
+#[derive(Debug, Clone, Copy)]
+struct CachedImpulse {
+    normal_impulse: Float,
+    tangent_impulse: Vector3,
+    rolling_impulse: Vector3,
+}
+
+/// A contact point prepared for the solver. Vectors are in world coordinates.
+#[derive(Debug, Clone, Copy)]
+struct ContactConstraint {
+    key: ContactKey,
+    body1: usize,
+    body2: usize,
+    r1: Vector3,
+    r2: Vector3,
+    /// Points from body1 to body2.
+    normal: Vector3,
+    tangents: [Vector3; 2],
+    normal_mass: Float,
+    tangent_mass: [Float; 2],
+    /// Static or dynamic friction, depending on whether the bodies slide at the start of the step.
+    friction: Float,
+    rolling_resistance: Float,
+    /// Inverse of the sum of the inverse inertias, to stop the relative rotation with.
+    rolling_mass: Matrix3,
+    /// Separating speed the normal impulse aims at.
+    restitution_bias: Float,
+    /// Separating pseudo speed which resolves the penetration.
+    penetration_bias: Float,
+    normal_impulse: Float,
+    tangent_impulse: [Float; 2],
+    /// Angular impulse applied to body2 against the rotation relative to body1.
+    rolling_impulse: Vector3,
+    pseudo_impulse: Float,
+}
+
+impl ContactConstraint {
+    fn friction_impulse(&self) -> Vector3 {
+        self.tangents[0] * self.tangent_impulse[0] + self.tangents[1] * self.tangent_impulse[1]
+    }
+
+    fn accumulated_impulse(&self) -> Vector3 {
+        self.normal * self.normal_impulse + self.friction_impulse()
+    }
+
+    /// Returns the velocity of body2 relative to body1 at the contact point.
+    fn relative_velocity(&self, bodies: &[SolverBody]) -> Vector3 {
+        bodies[self.body2].velocity_at(&self.r2) - bodies[self.body1].velocity_at(&self.r1)
+    }
+
+    fn apply_impulse(&self, bodies: &mut [SolverBody], impulse: &Vector3) {
+        bodies[self.body1].apply_impulse(&(-impulse), &self.r1);
+        bodies[self.body2].apply_impulse(impulse, &self.r2);
+    }
+
+    fn apply_rolling_impulse(&self, bodies: &mut [SolverBody], impulse: &Vector3) {
+        bodies[self.body1].apply_angular_impulse(&(-impulse));
+        bodies[self.body2].apply_angular_impulse(impulse);
+    }
+
+    fn solve_velocity(&mut self, bodies: &mut [SolverBody]) {
+        if self.rolling_resistance > 0.0 {
+            let max_rolling = self.rolling_resistance * self.normal_impulse;
+            let relative =
+                bodies[self.body2].angular_velocity - bodies[self.body1].angular_velocity;
+            let mut accumulated = self.rolling_impulse - self.rolling_mass * relative;
+            if accumulated.magnitude() > max_rolling {
+                accumulated *= max_rolling / accumulated.magnitude();
+            }
+            let lambda = accumulated - self.rolling_impulse;
+            self.rolling_impulse = accumulated;
+            self.apply_rolling_impulse(bodies, &lambda);
+        }
+
+        // friction first, so the normal impulse has the last word on penetration.
+        let max_friction = self.friction * self.normal_impulse;
+        for i in 0..2 {
+            let vt = self.relative_velocity(bodies).dot(&self.tangents[i]);
+            let lambda = -vt * self.tangent_mass[i];
+            let accumulated = (self.tangent_impulse[i] + lambda).clamp(-max_friction, max_friction);
+            let lambda = accumulated - self.tangent_impulse[i];
+            self.tangent_impulse[i] = accumulated;
+            self.apply_impulse(bodies, &(self.tangents[i] * lambda));
+        }
+
+        let vn = self.relative_velocity(bodies).dot(&self.normal);
+        let lambda = (self.restitution_bias - vn) * self.normal_mass;
+        let accumulated = (self.normal_impulse + lambda).max(0.0);
+        let lambda = accumulated - self.normal_impulse;
+        self.normal_impulse = accumulated;
+        self.apply_impulse(bodies, &(self.normal * lambda));
+    }
+
+    fn solve_position(&mut self, bodies: &mut [SolverBody]) {
+        let (sb1, sb2) = (&bodies[self.body1], &bodies[self.body2]);
+        let vn =
+            (sb2.pseudo_velocity_at(&self.r2) - sb1.pseudo_velocity_at(&self.r1)).dot(&self.normal);
+        let lambda = (self.penetration_bias - vn) * self.normal_mass;
+        let accumulated = (self.pseudo_impulse + lambda).max(0.0);
+        let lambda = accumulated - self.pseudo_impulse;
+        self.pseudo_impulse = accumulated;
+        let impulse = self.normal * lambda;
+        bodies[self.body1].apply_pseudo_impulse(&(-impulse), &self.r1);
+        bodies[self.body2].apply_pseudo_impulse(&impulse, &self.r2);
+    }
+}
+
+/// Identifies a row of a joint across steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct JointRowKey {
+    joint: JointId,
+    row: usize,
+}
+
+/// Rows of a joint by what they constrain, so their impulses can be cached.
+const JOINT_ROW_LINEAR: usize = 0;
+const JOINT_ROW_ANGULAR: usize = 3;
+const JOINT_ROW_LOWER_LIMIT: usize = 6;
+const JOINT_ROW_UPPER_LIMIT: usize = 7;
+const JOINT_ROW_MOTOR: usize = 8;
+
+/// One degree of freedom removed by a joint. Vectors are in world coordinates.
+/// The velocity along the row is the dot product of the jacobian and the body velocities.
+#[derive(Debug, Clone, Copy)]
+struct JointRow {
+    key: JointRowKey,
+    body1: usize,
+    body2: usize,
+    linear1: Vector3,
+    angular1: Vector3,
+    linear2: Vector3,
+    angular2: Vector3,
+    mass: Float,
+    /// Velocity along the row the impulse aims at.
+    target_velocity: Float,
+    min_impulse: Float,
+    max_impulse: Float,
+    impulse: Float,
+}
+
+impl JointRow {
+    fn velocity(&self, bodies: &[SolverBody]) -> Float {
+        let (sb1, sb2) = (&bodies[self.body1], &bodies[self.body2]);
+        self.linear1.dot(&sb1.linear_velocity)
+            + self.angular1.dot(&sb1.angular_velocity)
+            + self.linear2.dot(&sb2.linear_velocity)
+            + self.angular2.dot(&sb2.angular_velocity)
+    }
+
+    fn apply_impulse(&self, bodies: &mut [SolverBody], lambda: Float) {
+        let sb1 = &mut bodies[self.body1];
+        sb1.linear_velocity += sb1.inv_mass * self.linear1 * lambda;
+        sb1.angular_velocity += sb1.inv_inertia * self.angular1 * lambda;
+        let sb2 = &mut bodies[self.body2];
+        sb2.linear_velocity += sb2.inv_mass * self.linear2 * lambda;
+        sb2.angular_velocity += sb2.inv_inertia * self.angular2 * lambda;
+    }
+
+    fn solve_velocity(&mut self, bodies: &mut [SolverBody]) {
+        let lambda = (self.target_velocity - self.velocity(bodies)) * self.mass;
+        let accumulated = (self.impulse + lambda).clamp(self.min_impulse, self.max_impulse);
+        let lambda = accumulated - self.impulse;
+        self.impulse = accumulated;
+        self.apply_impulse(bodies, lambda);
+    }
+}
+
+/// Returns the lowest velocity toward a limit which is `error` away.
+/// The limit may be reached in this step but not crossed, and a part of the error
+/// is corrected once it has been crossed.
+/// Limit rows only push away from the limit, so they have no effect far from it.
+fn limit_velocity(error: Float, delta_time: Float) -> Float {
+    if error > 0.0 {
+        -error / delta_time
+    } else {
+        -BAUMGARTE * error / delta_time
+    }
+}
+
+/// Iterative sequential impulse solver for contacts and joints.
+/// All the contacts of a step are gathered first, then normal and friction impulses are
+/// iterated with accumulated impulse clamping. Penetration is resolved with split impulses.
+/// Joints are rows of the same iterations, with their position errors corrected by velocity.
+/// The impulses of a step are cached per body pair and contact feature, or per joint row,
+/// and the solver starts from them in the next step.
+#[derive(Debug)]
+pub struct ContactSolver {
+    velocity_iterations: usize,
+    position_iterations: usize,
+    bodies: Vec<SolverBody>,
+    body_indices: HashMap<RigidBodyId, usize>,
+    constraints: Vec<ContactConstraint>,
+    cache: HashMap<ContactKey, CachedImpulse>,
+    joint_rows: Vec<JointRow>,
+    joint_cache: HashMap<JointRowKey, Float>,
+}
+
+impl ContactSolver {
+    pub fn new() -> Self {
+        Self {
+            velocity_iterations: 10,
+            position_iterations: 4,
+            bodies: Vec::new(),
+            body_indices: HashMap::new(),
+            constraints: Vec::new(),
+            cache: HashMap::new(),
+            joint_rows: Vec::new(),
+            joint_cache: HashMap::new(),
+        }
+    }
+
+    pub fn get_velocity_iterations(&self) -> usize {
+        self.velocity_iterations
+    }
+    pub fn set_velocity_iterations(&mut self, iterations: usize) {
+        self.velocity_iterations = iterations;
+    }
+    pub fn get_position_iterations(&self) -> usize {
+        self.position_iterations
+    }
+    pub fn set_position_iterations(&mut self, iterations: usize) {
+        self.position_iterations = iterations;
+    }
+
+    /// Removes the contacts and joints of the previous step,
+    /// keeping their impulses to warm start the next.
+    /// Contacts and joint rows which aren't added again in the next step are forgotten.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        for constraint in &self.constraints {
+            let cached = CachedImpulse {
+                normal_impulse: constraint.normal_impulse,
+                tangent_impulse: constraint.friction_impulse(),
+                rolling_impulse: constraint.rolling_impulse,
+            };
+            self.cache.insert(constraint.key, cached);
+        }
+        self.joint_cache.clear();
+        for row in &self.joint_rows {
+            self.joint_cache.insert(row.key, row.impulse);
+        }
+        self.bodies.clear();
+        self.body_indices.clear();
+        self.constraints.clear();
+        self.joint_rows.clear();
+    }
+
+    fn body_index(&mut self, id: RigidBodyId, body: &RigidBody) -> usize {
+        let bodies = &mut self.bodies;
+        *self.body_indices.entry(id).or_insert_with(|| {
+            bodies.push(SolverBody::new(body));
+            bodies.len() - 1
+        })
+    }
+
+    /// Adds a contact between two bodies. The contact is in the form returned by `b1.contact(b2)`.
+    pub fn add_contact(
+        &mut self,
+        (id1, b1): (RigidBodyId, &RigidBody),
+        (id2, b2): (RigidBodyId, &RigidBody),
+        contact: &Contact,
+        delta_time: Float,
+    ) {
+        let body1 = self.body_index(id1, b1);
+        let body2 = self.body_index(id2, b2);
+        let (sb1, sb2) = (&self.bodies[body1], &self.bodies[body2]);
+
+        // both bodies are pushed at the same point, otherwise friction would add a spurious torque.
+        let pt1 = b1.local_to_world_point(&contact.point1);
+        let pt2 = b2.local_to_world_point(&contact.point2);
+        let point = pt1 + (pt2 - pt1) * 0.5;
+        let r1 = point - b1.get_center_of_mass_world();
+        let r2 = point - b2.get_center_of_mass_world();
+        let normal = b1.local_to_world_vector(&contact.normal1);
+        let tangent1 = if normal.x.abs() > 0.57 {
+            Vector3::new(normal.y, -normal.x, 0.0).normalize()
+        } else {
+            Vector3::new(0.0, normal.z, -normal.y).normalize()
+        };
+        let tangents = [tangent1, normal.cross(&tangent1)];
+
+        let inv_mass = |dir: &Vector3| {
+            let k = sb1.inv_effective_mass(&r1, dir) + sb2.inv_effective_mass(&r2, dir);
+            if k > 0.0 {
+                1.0 / k
+            } else {
+                0.0
+            }
+        };
+        let normal_mass = inv_mass(&normal);
+        let tangent_mass = [inv_mass(&tangents[0]), inv_mass(&tangents[1])];
+
+        let relative_velocity = sb2.velocity_at(&r2) - sb1.velocity_at(&r1);
+        let normal_velocity = relative_velocity.dot(&normal);
+        let material = b1.get_material().combine(b2.get_material());
+        let sliding_velocity = relative_velocity - normal * normal_velocity;
+        let friction = if sliding_velocity.magnitude() > STATIC_FRICTION_VELOCITY {
+            material.dynamic_friction
+        } else {
+            material.static_friction
+        };
+        let rolling_mass = (sb1.inv_inertia + sb2.inv_inertia)
+            .try_inverse()
+            .unwrap_or_else(Matrix3::zeros);
+        let restitution_bias = if contact.separation_distance > 0.0 {
+            // speculative contact, the bodies may approach until they touch.
+            -contact.separation_distance / delta_time
+        } else if normal_velocity < -RESTITUTION_THRESHOLD {
+            -material.restitution * normal_velocity
+        } else {
+            0.0
+        };
+        let penetration = -contact.separation_distance - ALLOWED_PENETRATION;
+        let penetration_bias = BAUMGARTE * penetration.max(0.0) / delta_time;
+
+        let key = ContactKey {
+            body1: id1,
+            body2: id2,
+            feature: contact.feature,
+        };
+        let (normal_impulse, tangent_impulse, rolling_impulse) = match self.cache.get(&key) {
+            Some(cached) => (
+                cached.normal_impulse,
+                tangents.map(|t| t.dot(&cached.tangent_impulse)),
+                cached.rolling_impulse,
+            ),
+            None => (0.0, [0.0; 2], Vector3::zeros()),
+        };
+
+        self.constraints.push(ContactConstraint {
+            key,
+            body1,
+            body2,
+            r1,
+            r2,
+            normal,
+            tangents,
+            normal_mass,
+            tangent_mass,
+            friction,
+            rolling_resistance: material.rolling_resistance,
+            rolling_mass,
+            restitution_bias,
+            penetration_bias,
+            normal_impulse,
+            tangent_impulse,
+            rolling_impulse,
+            pseudo_impulse: 0.0,
+        });
+    }
+
+    /// Returns the bodies and the normal impulse of each contact of the last solve.
+    pub fn contact_impulses(&self) -> impl Iterator<Item = (RigidBodyId, RigidBodyId, Float)> + '_ {
+        self.constraints
+            .iter()
+            .map(|c| (c.key.body1, c.key.body2, c.normal_impulse))
+    }
+
+    /// Adds the rows of a joint between two bodies.
+    pub fn add_joint(
+        &mut self,
+        id: JointId,
+        joint: &Joint,
+        (id1, b1): (RigidBodyId, &RigidBody),
+        (id2, b2): (RigidBodyId, &RigidBody),
+        delta_time: Float,
+    ) {
+        let body1 = self.body_index(id1, b1);
+        let body2 = self.body_index(id2, b2);
+        let (sb1, sb2) = (&self.bodies[body1], &self.bodies[body2]);
+
+        let (f1, f2) = joint.world_frames(b1, b2);
+        let axes1 = [Vector3::x(), Vector3::y(), Vector3::z()].map(|a| f1.rotation * a);
+        let world_axes = [Vector3::x(), Vector3::y(), Vector3::z()];
+        // both bodies are pushed at the anchor of body2, like contacts at a single point.
+        let anchor = Point3::from(f2.translation.vector);
+        let r1 = anchor - b1.get_center_of_mass_world();
+        let r2 = anchor - b2.get_center_of_mass_world();
+        let offset = f2.translation.vector - f1.translation.vector;
+
+        let row = |row: usize, linear: Vector3, angular: Vector3, target_velocity: Float| {
+            let (linear1, angular1) = (-linear, -(r1.cross(&linear) + angular));
+            let (linear2, angular2) = (linear, r2.cross(&linear) + angular);
+            let k = sb1.inv_mass * linear1.magnitude_squared()
+                + angular1.dot(&(sb1.inv_inertia * angular1))
+                + sb2.inv_mass * linear2.magnitude_squared()
+                + angular2.dot(&(sb2.inv_inertia * angular2));
+            JointRow {
+                key: JointRowKey { joint: id, row },
+                body1,
+                body2,
+                linear1,
+                angular1,
+                linear2,
+                angular2,
+                mass: if k > 0.0 { 1.0 / k } else { 0.0 },
+                target_velocity,
+                min_impulse: -FLOAT_MAX,
+                max_impulse: FLOAT_MAX,
+                impulse: 0.0,
+            }
+        };
+        let correction = |error: Float| -BAUMGARTE * error / delta_time;
+        let mut rows = Vec::new();
+
+        // anchors.
+        let linear_axes = match joint.get_type() {
+            JointType::Prismatic { .. } => &axes1[1..],
+            _ => &world_axes[..],
+        };
+        for (i, axis) in linear_axes.iter().enumerate() {
+            let target = correction(offset.dot(axis));
+            rows.push(row(JOINT_ROW_LINEAR + i, *axis, Vector3::zeros(), target));
+        }
+
+        // rotations.
+        match joint.get_type() {
+            JointType::Spherical => {}
+            JointType::Revolute { .. } => {
+                // the axes of the frames stay parallel.
+                let error = axes1[0].cross(&(f2.rotation * Vector3::x()));
+                for (i, axis) in axes1[1..].iter().enumerate() {
+                    let target = correction(error.dot(axis));
+                    rows.push(row(JOINT_ROW_ANGULAR + i, Vector3::zeros(), *axis, target));
+                }
+            }
+            JointType::Prismatic { .. } | JointType::Weld => {
+                let error = (f2.rotation * f1.rotation.inverse()).scaled_axis();
+                for (i, axis) in world_axes.iter().enumerate() {
+                    let target = correction(error.dot(axis));
+                    rows.push(row(JOINT_ROW_ANGULAR + i, Vector3::zeros(), *axis, target));
+                }
+            }
+        }
+
+        // limits and motor along the axis.
+        let (limits, position, linear, angular) = match joint.get_type() {
+            JointType::Revolute { limits, .. } => {
+                (*limits, joint.angle(b1, b2), Vector3::zeros(), axes1[0])
+            }
+            JointType::Prismatic { limits } => {
+                (*limits, offset.dot(&axes1[0]), axes1[0], Vector3::zeros())
+            }
+            _ => (None, 0.0, Vector3::zeros(), Vector3::zeros()),
+        };
+        if let Some((lower, upper)) = limits {
+            let mut lower_row = row(
+                JOINT_ROW_LOWER_LIMIT,
+                linear,
+                angular,
+                limit_velocity(position - lower, delta_time),
+            );
+            lower_row.min_impulse = 0.0;
+            rows.push(lower_row);
+            let mut upper_row = row(
+                JOINT_ROW_UPPER_LIMIT,
+                linear,
+                angular,
+                -limit_velocity(upper - position, delta_time),
+            );
+            upper_row.max_impulse = 0.0;
+            rows.push(upper_row);
+        }
+        if let JointType::Revolute {
+            motor: Some(motor), ..
+        } = joint.get_type()
+        {
+            let mut motor_row = row(JOINT_ROW_MOTOR, linear, angular, motor.target_velocity);
+            motor_row.max_impulse = motor.max_torque * delta_time;
+            motor_row.min_impulse = -motor_row.max_impulse;
+            rows.push(motor_row);
+        }
+
+        for mut row in rows {
+            if let Some(impulse) = self.joint_cache.get(&row.key) {
+                row.impulse = impulse.clamp(row.min_impulse, row.max_impulse);
+            }
+            self.joint_rows.push(row);
+        }
+    }
+
+    /// Iterates the impulses of all the contacts and joints added since the last clear.
+    pub fn solve(&mut self) {
+        // warm start with the impulses of the previous step.
+        for constraint in &self.constraints {
+            constraint.apply_impulse(&mut self.bodies, &constraint.accumulated_impulse());
+            constraint.apply_rolling_impulse(&mut self.bodies, &constraint.rolling_impulse);
+        }
+        for row in &self.joint_rows {
+            row.apply_impulse(&mut self.bodies, row.impulse);
+        }
+
+        // sweeping back and forth keeps the order of the contacts from biasing the result.
+        let len = self.constraints.len();
+        let order = |iteration: usize, k: usize| {
+            if iteration.is_multiple_of(2) {
+                k
+            } else {
+                len - 1 - k
+            }
+        };
+        for iteration in 0..self.velocity_iterations {
+            // joints first, so contacts have the last word on penetration.
+            for row in &mut self.joint_rows {
+                row.solve_velocity(&mut self.bodies);
+            }
+            for k in 0..len {
+                self.constraints[order(iteration, k)].solve_velocity(&mut self.bodies);
+            }
+        }
+        for iteration in 0..self.position_iterations {
+            for k in 0..len {
+                self.constraints[order(iteration, k)].solve_position(&mut self.bodies);
+            }
+        }
+    }
+
+    /// Writes the solved velocities back to the body.
+    /// Returns the pseudo linear and angular velocities the body has to be moved with in this step,
+    /// or None if the body had no contacts or joints.
+    pub fn write_back(&self, id: RigidBodyId, body: &mut RigidBody) -> Option<(Vector3, Vector3)> {
+        let sb = &self.bodies[*self.body_indices.get(&id)?];
+        if body.get_inv_mass() == 0.0 {
+            return None;
+        }
+        body.set_linear_velocity(&sb.linear_velocity);
+        body.set_angular_velocity(&sb.angular_velocity);
+        Some((sb.pseudo_linear_velocity, sb.pseudo_angular_velocity))
+    }
+}
+
+impl Default for ContactSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,154 @@
+use crate::math::*;
+use crate::physics::*;
+
+const TOI_MAX_ITERATIONS: usize = 32;
+
+/// Motion of a body over a step with constant velocities,
+/// rotating around its center of mass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Motion {
+    pub position: Isometry3,
+    /// Center of mass in the body's coordinates.
+    pub center_of_mass: Point3,
+    pub linear_velocity: Vector3,
+    pub angular_velocity: Vector3,
+}
+
+impl Motion {
+    pub fn new(body: &RigidBody) -> Self {
+        Self {
+            position: *body.get_position(),
+            center_of_mass: body.get_center_of_mass_local(),
+            linear_velocity: *body.get_linear_velocity(),
+            angular_velocity: *body.get_angular_velocity(),
+        }
+    }
+
+    pub fn new_static(position: Isometry3) -> Self {
+        Self {
+            position,
+            center_of_mass: Point3::origin(),
+            linear_velocity: Vector3::zeros(),
+            angular_velocity: Vector3::zeros(),
+        }
+    }
+
+    /// Returns the position after time t.
+    pub fn position_at(&self, t: Float) -> Isometry3 {
+        let mut position = self.position;
+        position.append_translation_mut(&Translation3::from(self.linear_velocity * t));
+        position.append_rotation_wrt_point_mut(
+            &UnitQuaternion::from_scaled_axis(self.angular_velocity * t),
+            &position.transform_point(&self.center_of_mass),
+        );
+        position
+    }
+
+    /// Returns an upper bound of the speed of any point of the shape.
+    fn max_speed(&self, shape: &ShapeType) -> Float {
+        let angular_speed = self.angular_velocity.magnitude();
+        if angular_speed == 0.0 {
+            return self.linear_velocity.magnitude();
+        }
+        let sphere = shape.build_bounding_sphere(&Isometry3::identity());
+        let radius = (sphere.center - self.center_of_mass).magnitude() + sphere.radius;
+        self.linear_velocity.magnitude() + angular_speed * radius
+    }
+}
+
+/// A body hit by a shape cast. Vectors are in world coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapeHit {
+    pub body: RigidBodyId,
+    /// Distance the shape travelled before touching the body.
+    pub toi: Float,
+    /// Point on the body's surface.
+    pub point: Point3,
+    /// Normal of the body's surface at the point, facing the shape.
+    pub normal: UnitVector3,
+}
+
+/// Returns the first time in [0, max_time] at which the shapes get closer than target_distance,
+/// found with conservative advancement.
+/// Returns None if they don't, or if they are already that close at the start,
+/// since such contacts are left to the solver.
+pub fn time_of_impact(
+    shape1: &ShapeType,
+    motion1: &Motion,
+    shape2: &ShapeType,
+    motion2: &Motion,
+    max_time: Float,
+    target_distance: Float,
+) -> Option<Float> {
+    let relative_speed = (motion2.linear_velocity - motion1.linear_velocity).magnitude();
+    let angular_speed1 = motion1.max_speed(shape1) - motion1.linear_velocity.magnitude();
+    let angular_speed2 = motion2.max_speed(shape2) - motion2.linear_velocity.magnitude();
+    let speed = relative_speed + angular_speed1 + angular_speed2;
+    if speed <= 0.0 {
+        return None;
+    }
+
+    let mut t = 0.0;
+    for iteration in 0..TOI_MAX_ITERATIONS {
+        let pos12 = motion1.position_at(t).inverse() * motion2.position_at(t);
+        // the shapes can't close a larger gap in the time left.
+        let max_distance = speed * (max_time - t) + target_distance;
+        let distance = shape1
+            .closest_points(shape2, &pos12, max_distance)
+            .map_or(max_distance, |c| c.separation_distance.max(0.0));
+        if distance <= target_distance {
+            return if iteration == 0 { None } else { Some(t) };
+        }
+        if distance >= max_distance {
+            return None;
+        }
+        // aim a bit below the target so that it is crossed in a few iterations.
+        t += (distance - target_distance * 0.5) / speed;
+        if t > max_time {
+            return None;
+        }
+    }
+    Some(t)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_time_of_impact() {
+        // a small sphere moving fast toward a thin wall.
+        let sphere = ShapeType::Sphere(Sphere::new(0.05));
+        let wall = ShapeType::Cube(Cube::new(Vector3::new(0.01, 1.0, 1.0)));
+        let mut motion1 = Motion::new_static(Isometry3::identity());
+        motion1.linear_velocity = Vector3::new(100.0, 0.0, 0.0);
+        let motion2 = Motion::new_static(Isometry3::translation(2.0, 0.0, 0.0));
+
+        // the sphere touches the wall after travelling 2.0 - 0.01 - 0.05.
+        let toi = time_of_impact(&sphere, &motion1, &wall, &motion2, 0.1, 1.0e-3).unwrap();
+        let expected = (2.0 - 0.01 - 0.05) / 100.0;
+        assert!(toi <= expected);
+        assert!(expected - toi < 1.0e-4);
+
+        // too slow to reach the wall in time.
+        assert_eq!(
+            time_of_impact(&sphere, &motion1, &wall, &motion2, 0.01, 1.0e-3),
+            None
+        );
+        // moving away.
+        motion1.linear_velocity = -motion1.linear_velocity;
+        assert_eq!(
+            time_of_impact(&sphere, &motion1, &wall, &motion2, 0.1, 1.0e-3),
+            None
+        );
+
+        // a spinning rod hits the wall even though its center stays still.
+        let rod = ShapeType::Cube(Cube::new(Vector3::new(1.0, 0.05, 0.05)));
+        let mut motion1 = Motion::new_static(Isometry3::translation(1.5, 0.0, 0.0));
+        motion1.position.rotation = UnitQuaternion::from_euler_angles(0.0, 0.0, PI / 2.0);
+        motion1.angular_velocity = Vector3::new(0.0, 0.0, 10.0);
+        let motion2 = Motion::new_static(Isometry3::translation(2.0, 0.0, -2.0));
+        let wall = ShapeType::Cube(Cube::new(Vector3::new(0.2, 1.0, 2.5)));
+        assert!(time_of_impact(&rod, &motion1, &wall, &motion2, 0.2, 1.0e-3).is_some());
+    }
+}
@@ -0,0 +1,373 @@
+use super::*;
+use crate::math::*;
+
+/// Speed along a wheel under which its brake force fades out, so a braked car comes to rest
+/// instead of jittering.
+const BRAKE_FADE_VELOCITY: Float = 0.5;
+
+/// A wheel of a vehicle, modelled as a suspension ray cast down from the chassis.
+/// The suspension and tire forces are given per unit of the chassis mass,
+/// so the defaults suit a chassis of any mass.
+#[derive(Debug, Clone)]
+pub struct Wheel {
+    /// Top of the suspension, in chassis coordinates.
+    anchor: Point3,
+    radius: Float,
+    /// Length of the suspension when it carries no load.
+    rest_length: Float,
+    stiffness: Float,
+    damping: Float,
+    /// Tire grip, limiting the sum of the forces along the ground to grip * suspension force.
+    grip: Float,
+    steered: bool,
+    driven: bool,
+
+    /// How much shorter than rest_length the suspension is, 0 when the wheel is in the air.
+    compression: Float,
+    contact: Option<RayHit>,
+    /// Angle the wheel has rolled by.
+    rotation: Float,
+}
+
+impl Wheel {
+    pub fn new(anchor: Point3, radius: Float) -> Self {
+        Self {
+            anchor,
+            radius,
+            rest_length: 0.5,
+            stiffness: 40.0,
+            damping: 4.5,
+            grip: 1.0,
+            steered: false,
+            driven: false,
+            compression: 0.0,
+            contact: None,
+            rotation: 0.0,
+        }
+    }
+
+    pub fn get_anchor(&self) -> &Point3 {
+        &self.anchor
+    }
+    pub fn set_anchor(&mut self, anchor: &Point3) {
+        self.anchor = *anchor;
+    }
+    pub fn get_radius(&self) -> Float {
+        self.radius
+    }
+    pub fn set_radius(&mut self, radius: Float) {
+        self.radius = radius;
+    }
+    pub fn get_rest_length(&self) -> Float {
+        self.rest_length
+    }
+    pub fn set_rest_length(&mut self, length: Float) {
+        self.rest_length = length;
+    }
+    pub fn get_stiffness(&self) -> Float {
+        self.stiffness
+    }
+    pub fn set_stiffness(&mut self, stiffness: Float) {
+        self.stiffness = stiffness;
+    }
+    pub fn get_damping(&self) -> Float {
+        self.damping
+    }
+    pub fn set_damping(&mut self, damping: Float) {
+        self.damping = damping;
+    }
+    pub fn get_grip(&self) -> Float {
+        self.grip
+    }
+    pub fn set_grip(&mut self, grip: Float) {
+        self.grip = grip;
+    }
+    pub fn is_steered(&self) -> bool {
+        self.steered
+    }
+    pub fn set_steered(&mut self, steered: bool) {
+        self.steered = steered;
+    }
+    pub fn is_driven(&self) -> bool {
+        self.driven
+    }
+    pub fn set_driven(&mut self, driven: bool) {
+        self.driven = driven;
+    }
+
+    pub fn get_compression(&self) -> Float {
+        self.compression
+    }
+    /// Returns where the wheel touches the ground, if it does.
+    pub fn get_contact(&self) -> Option<&RayHit> {
+        self.contact.as_ref()
+    }
+    pub fn get_rotation(&self) -> Float {
+        self.rotation
+    }
+}
+
+/// A chassis body carried by wheels, each a suspension ray with a spring, a damper and
+/// tire friction, so no wheel bodies are simulated.
+/// The chassis faces its z axis, with its y axis up.
+/// Inputs are turned into forces on the chassis at the wheel contacts,
+/// given per unit of the chassis mass like those of the wheels.
+#[derive(Debug, Clone)]
+pub struct Vehicle {
+    chassis: RigidBodyId,
+    wheels: Vec<Wheel>,
+    /// Angle of the steered wheels at full steering, in radians.
+    max_steering_angle: Float,
+    /// Forward force of the engine at full throttle, shared by the driven wheels.
+    engine_force: Float,
+    /// Force of the brake of each wheel at full brake.
+    brake_force: Float,
+    steering: Float,
+    throttle: Float,
+    brake: Float,
+}
+
+impl Vehicle {
+    pub fn new(chassis: RigidBodyId) -> Self {
+        Self {
+            chassis,
+            wheels: Vec::new(),
+            max_steering_angle: 0.5,
+            engine_force: 8.0,
+            brake_force: 3.0,
+            steering: 0.0,
+            throttle: 0.0,
+            brake: 0.0,
+        }
+    }
+
+    pub fn get_chassis(&self) -> RigidBodyId {
+        self.chassis
+    }
+    /// Adds a wheel and returns its index.
+    pub fn add_wheel(&mut self, wheel: Wheel) -> usize {
+        self.wheels.push(wheel);
+        self.wheels.len() - 1
+    }
+    pub fn get_wheels(&self) -> &[Wheel] {
+        &self.wheels
+    }
+    pub fn get_wheel_mut(&mut self, index: usize) -> Option<&mut Wheel> {
+        self.wheels.get_mut(index)
+    }
+    pub fn get_max_steering_angle(&self) -> Float {
+        self.max_steering_angle
+    }
+    pub fn set_max_steering_angle(&mut self, angle: Float) {
+        self.max_steering_angle = angle;
+    }
+    pub fn get_engine_force(&self) -> Float {
+        self.engine_force
+    }
+    pub fn set_engine_force(&mut self, force: Float) {
+        self.engine_force = force;
+    }
+    pub fn get_brake_force(&self) -> Float {
+        self.brake_force
+    }
+    pub fn set_brake_force(&mut self, force: Float) {
+        self.brake_force = force;
+    }
+
+    pub fn get_steering(&self) -> Float {
+        self.steering
+    }
+    /// Sets the steering in [-1, 1], positive to the left.
+    pub fn set_steering(&mut self, steering: Float) {
+        self.steering = steering.clamp(-1.0, 1.0);
+    }
+    pub fn get_throttle(&self) -> Float {
+        self.throttle
+    }
+    /// Sets the throttle in [-1, 1], negative to reverse.
+    pub fn set_throttle(&mut self, throttle: Float) {
+        self.throttle = throttle.clamp(-1.0, 1.0);
+    }
+    pub fn get_brake(&self) -> Float {
+        self.brake
+    }
+    /// Sets the brake in [0, 1].
+    pub fn set_brake(&mut self, brake: Float) {
+        self.brake = brake.clamp(0.0, 1.0);
+    }
+
+    /// Returns the position of the center of a wheel in world coordinates,
+    /// steered and rolled, to draw it with.
+    pub fn wheel_position(&self, index: usize, chassis: &RigidBody) -> Option<Isometry3> {
+        let wheel = self.wheels.get(index)?;
+        let length = wheel.rest_length - wheel.compression;
+        let center = wheel.anchor - Vector3::y() * length;
+        let steering = if wheel.steered {
+            self.steering * self.max_steering_angle
+        } else {
+            0.0
+        };
+        let rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), steering)
+            * UnitQuaternion::from_axis_angle(&Vector3::x_axis(), wheel.rotation);
+        let local = Isometry3::from_parts(Translation3::from(center.coords), rotation);
+        Some(chassis.get_position() * local)
+    }
+
+    /// Casts the suspension rays and applies the suspension, engine, brake and tire forces
+    /// to the chassis. Call it before each `PhysicsWorld::update`, with its delta_time.
+    pub fn update(&mut self, world: &mut PhysicsWorld, delta_time: Float) {
+        let Some(chassis) = world.get(self.chassis) else {
+            return;
+        };
+        let position = *chassis.get_position();
+        let mass = match chassis.get_inv_mass() {
+            inv_mass if inv_mass > 0.0 => 1.0 / inv_mass,
+            _ => return,
+        };
+        let up = position.rotation * Vector3::y_axis();
+        let forward = position.rotation * Vector3::z();
+
+        let mut hits = Vec::with_capacity(self.wheels.len());
+        for wheel in &self.wheels {
+            let origin = position.transform_point(&wheel.anchor);
+            let filter = |id: RigidBodyId, other: &RigidBody| {
+                id != self.chassis && !other.is_sensor() && chassis.can_collide_with(other)
+            };
+            let max_toi = wheel.rest_length + wheel.radius;
+            hits.push(world.cast_ray(&origin, &-up, max_toi, filter));
+        }
+        let driven = self.wheels.iter().filter(|w| w.driven).count().max(1) as Float;
+        let grounded = hits.iter().filter(|hit| hit.is_some()).count().max(1) as Float;
+
+        let mut forces = Vec::new();
+        for (wheel, hit) in self.wheels.iter_mut().zip(hits) {
+            wheel.contact = hit;
+            let Some(hit) = hit else {
+                wheel.compression = 0.0;
+                continue;
+            };
+            let chassis = world.get(self.chassis).unwrap();
+            let r = hit.point - chassis.get_center_of_mass_world();
+            let velocity = chassis.get_linear_velocity() + chassis.get_angular_velocity().cross(&r);
+
+            // spring and damper along the suspension, which only pushes.
+            wheel.compression = (wheel.rest_length + wheel.radius - hit.distance).max(0.0);
+            let compression_velocity = -velocity.dot(&up);
+            let suspension =
+                mass * (wheel.stiffness * wheel.compression + wheel.damping * compression_velocity);
+            let suspension = suspension.max(0.0);
+
+            // the tire frame, on the ground.
+            let normal = hit.normal.into_inner();
+            let steering = if wheel.steered {
+                self.steering * self.max_steering_angle
+            } else {
+                0.0
+            };
+            let heading = UnitQuaternion::from_axis_angle(&up, steering) * forward;
+            let Some(heading) = (heading - normal * heading.dot(&normal)).try_normalize(1.0e-6)
+            else {
+                continue;
+            };
+            let side = normal.cross(&heading);
+            let forward_velocity = velocity.dot(&heading);
+            let side_velocity = velocity.dot(&side);
+            wheel.rotation += forward_velocity / wheel.radius * delta_time;
+
+            let mut longitudinal = 0.0;
+            if wheel.driven {
+                longitudinal += mass * self.throttle * self.engine_force / driven;
+            }
+            let fade = (forward_velocity / BRAKE_FADE_VELOCITY).clamp(-1.0, 1.0);
+            longitudinal -= mass * self.brake * self.brake_force * fade;
+            // the side force stops the sideways slide, shared with the other wheels on the ground.
+            let lateral = -side_velocity * mass / (grounded * delta_time);
+            let mut traction = heading * longitudinal + side * lateral;
+            let max_traction = wheel.grip * suspension;
+            if traction.magnitude() > max_traction {
+                traction *= max_traction / traction.magnitude();
+            }
+            forces.push((up.into_inner() * suspension + traction, hit));
+        }
+
+        for (force, hit) in forces {
+            world
+                .get_mut(self.chassis)
+                .unwrap()
+                .apply_force_point_world(&force, &hit.point);
+            // the ground is pushed back, if it can move.
+            if let Some(ground) = world.get_mut(hit.body) {
+                if ground.get_inv_mass() != 0.0 {
+                    ground.apply_force_point_world(&-force, &hit.point);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_vehicle() {
+        let mut world = PhysicsWorld::new();
+        world.insert(RigidBody::new(
+            ShapeType::Plane(Plane::new(Vector3::y_axis(), 0.0)),
+            0.0,
+        ));
+        let mut chassis = RigidBody::new(
+            ShapeType::Cube(Cube::new(Vector3::new(1.0, 0.25, 2.0))),
+            1.0,
+        );
+        chassis.set_position(&Isometry3::translation(0.0, 1.0, 0.0));
+        let mut vehicle = Vehicle::new(world.insert(chassis));
+        for (x, z) in [(0.9, 1.5), (-0.9, 1.5), (0.9, -1.5), (-0.9, -1.5)] {
+            let mut wheel = Wheel::new(Point3::new(x, 0.0, z), 0.35);
+            wheel.set_steered(z > 0.0);
+            wheel.set_driven(z < 0.0);
+            vehicle.add_wheel(wheel);
+        }
+        let drive = |world: &mut PhysicsWorld, vehicle: &mut Vehicle, seconds: usize| {
+            for _ in 0..seconds * 60 {
+                vehicle.update(world, 1.0 / 60.0);
+                world.update(1.0 / 60.0);
+            }
+            world.get(vehicle.get_chassis()).unwrap().clone()
+        };
+
+        // the suspensions carry the chassis, each compressed by g / (4 * stiffness).
+        let chassis = drive(&mut world, &mut vehicle, 2);
+        assert!(vehicle
+            .get_wheels()
+            .iter()
+            .all(|w| w.get_contact().is_some()));
+        let expected = 0.35 + 0.5 - 9.8 / 160.0;
+        assert!((chassis.get_position().translation.y - expected).abs() < 0.01);
+        assert!(chassis.get_linear_velocity().magnitude() < 0.01);
+
+        vehicle.set_throttle(1.0);
+        let chassis = drive(&mut world, &mut vehicle, 2);
+        let velocity = chassis.get_linear_velocity();
+        assert!(velocity.z > 10.0);
+        assert!(velocity.x.abs() < 0.1);
+
+        // turning left, toward x.
+        vehicle.set_throttle(0.0);
+        vehicle.set_steering(1.0);
+        let chassis = drive(&mut world, &mut vehicle, 1);
+        let heading = chassis.get_position().rotation * Vector3::z();
+        assert!(heading.x > 0.5);
+        let velocity = chassis.get_linear_velocity();
+        assert!(velocity.normalize().dot(&heading) > 0.9);
+
+        vehicle.set_steering(0.0);
+        vehicle.set_brake(1.0);
+        let chassis = drive(&mut world, &mut vehicle, 5);
+        assert!(chassis.get_linear_velocity().magnitude() < 0.1);
+        assert!(vehicle
+            .get_wheels()
+            .iter()
+            .all(|w| w.get_contact().is_some()));
+    }
+}
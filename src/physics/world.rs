@@ -1,8 +1,18 @@
 use super::*;
 use crate::math::*;
 use generational_arena as ga;
+use std::collections::{HashMap, HashSet};
 
-#[derive(Debug, Clone, Copy)]
+const DEFAULT_FIXED_DELTA_TIME: Float = 1.0 / 60.0;
+const DEFAULT_MAX_SUBSTEPS: usize = 8;
+
+/// Gap at which a shape cast stops, in front of the body it hits.
+const SHAPE_CAST_TOLERANCE: Float = 1.0e-3;
+
+/// Number of impacts a ccd body can have in a step.
+const MAX_CCD_SUBSTEPS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct RigidBodyId {
     inner: ga::Index,
 }
@@ -11,80 +21,770 @@ impl RigidBodyId {
     fn new(inner: ga::Index) -> Self {
         Self { inner }
     }
+
+    /// Returns the index of the body's slot in the arena.
+    fn slot(&self) -> usize {
+        self.inner.into_raw_parts().0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct JointId {
+    inner: ga::Index,
+}
+
+impl JointId {
+    fn new(inner: ga::Index) -> Self {
+        Self { inner }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DistanceConstraintId {
+    inner: ga::Index,
+}
+
+impl DistanceConstraintId {
+    fn new(inner: ga::Index) -> Self {
+        Self { inner }
+    }
+}
+
+/// Contact between a pair of bodies gathered during an update.
+#[derive(Debug, Clone, Copy)]
+struct PairContact {
+    impulse: Float,
+    point: Point3,
+    /// Whether a contact point is penetrating, rather than only within the margin.
+    touching: bool,
 }
 
 #[derive(Debug)]
 pub struct PhysicsWorld {
-    bodies: ga::Arena<(RigidBody, BVHLeafId)>,
+    /// Bodies and their leaves in the BVH. Planes have no leaf.
+    bodies: ga::Arena<(RigidBody, Option<BVHLeafId>)>,
     bvh: BVH<AABB, RigidBodyId>,
+    /// Planes are infinite, so they are tested against every body instead of being in the BVH.
+    planes: Vec<RigidBodyId>,
+    joints: ga::Arena<Joint>,
+    distance_constraints: ga::Arena<DistanceConstraint>,
+    /// Pairs of bodies which never collide, in both orders.
+    ignored_pairs: HashSet<(RigidBodyId, RigidBodyId)>,
+    /// Pairs of overlapping bodies found in the last update where at least one is a sensor.
+    sensor_overlaps: Vec<(RigidBodyId, RigidBodyId)>,
+    /// Pairs of bodies in contact after the last update, ordered by id, with their contact point.
+    contact_pairs: HashMap<(RigidBodyId, RigidBodyId), Point3>,
+    collision_events: Vec<CollisionEvent>,
+    solver: ContactSolver,
     gravity: Vector3,
+    /// Time passed to `step` that hasn't been simulated yet.
+    accumulator: Float,
+    fixed_delta_time: Float,
+    max_substeps: usize,
 }
 
 impl PhysicsWorld {
     pub fn new() -> Self {
         let bodies = ga::Arena::new();
         let bvh = BVH::new();
+        let planes = Vec::new();
+        let solver = ContactSolver::new();
         let gravity = Vector3::new(0.0, -9.8, 0.0);
         Self {
             bodies,
             bvh,
+            planes,
+            joints: ga::Arena::new(),
+            distance_constraints: ga::Arena::new(),
+            ignored_pairs: HashSet::new(),
+            sensor_overlaps: Vec::new(),
+            contact_pairs: HashMap::new(),
+            collision_events: Vec::new(),
+            solver,
             gravity,
+            accumulator: 0.0,
+            fixed_delta_time: DEFAULT_FIXED_DELTA_TIME,
+            max_substeps: DEFAULT_MAX_SUBSTEPS,
+        }
+    }
+
+    /// Advances the simulation by the frame time in updates of the fixed delta time,
+    /// so that the result doesn't depend on the frame rate.
+    /// The time left over is carried to the next call.
+    /// If more than `max_substeps` updates are due, the rest of the time is dropped
+    /// so that a slow frame doesn't make the next ones slower.
+    /// Returns the fraction of an update left over, to interpolate the positions with.
+    /// The collision events of the previous step are cleared first.
+    pub fn step(&mut self, delta_time: Float) -> Float {
+        self.collision_events.clear();
+        self.accumulator += delta_time;
+        let mut substeps = 0;
+        while self.accumulator >= self.fixed_delta_time {
+            if substeps == self.max_substeps {
+                self.accumulator %= self.fixed_delta_time;
+                break;
+            }
+            self.update(self.fixed_delta_time);
+            self.accumulator -= self.fixed_delta_time;
+            substeps += 1;
         }
+        self.accumulator / self.fixed_delta_time
     }
 
     pub fn update(&mut self, delta_time: f32) {
-        for (body_id, (body, bounding_id)) in &mut self.bodies {
-            let body_id = RigidBodyId::new(body_id);
+        for (_, (body, _)) in &mut self.bodies {
+            let position = *body.get_position();
+            body.set_previous_position(&position);
+            if body.is_sleeping() {
+                continue;
+            }
             if body.get_inv_mass() != 0.0 {
                 body.apply_force_world(&(self.gravity / body.get_inv_mass()));
             }
-            if body.update(delta_time) {
-                self.bvh.remove(*bounding_id);
-                let new_bounding = body.get_aabb();
-                let new_bounding_id = self.bvh.insert(body_id, new_bounding);
-                *bounding_id = new_bounding_id;
-            }
-        }
-        //let ids = self.bodies.iter().map(|(id, _)| id).collect::<Vec<_>>();
-        //for i in 0..ids.len() {
-        //    for j in i + 1..ids.len() {
-        //        if let (Some(body1), Some(body2)) = self.bodies.get2_mut(ids[i], ids[j]) {
-        //            if let Some(contact) = body1.0.contact(&body2.0) {
-        //                resolve_contact(&mut body1.0, &mut body2.0, &contact);
-        //            }
-        //        }
-        //    }
-        //}
+            body.integrate_velocity(delta_time);
+        }
+        self.apply_distance_constraints(delta_time);
+
+        // gather all the contacts first so the result doesn't depend on the order of pairs.
         let mut overlaps = Vec::new();
         self.bvh.get_overlaps(&mut overlaps);
+        // the order of a pair changes when the BVH is rebuilt, keep it stable between steps.
+        let mut pairs: Vec<_> = overlaps
+            .into_iter()
+            .map(|(a, b)| {
+                if a.inner < b.inner {
+                    (*a, *b)
+                } else {
+                    (*b, *a)
+                }
+            })
+            .collect();
+        for plane_id in &self.planes {
+            for (body_id, (body, _)) in &self.bodies {
+                if body.get_inv_mass() != 0.0 {
+                    pairs.push((*plane_id, RigidBodyId::new(body_id)));
+                }
+            }
+        }
 
-        for (body_id1, body_id2) in &overlaps {
-            if let (Some((body1, _)), Some((body2, _))) =
-                self.bodies.get2_mut(body_id1.inner, body_id2.inner)
-            {
-                if let Some(contact) = body1.contact(body2) {
-                    resolve_contact(body1, body2, &contact);
+        let mut jointed = HashSet::new();
+        for (_, joint) in &self.joints {
+            if !joint.get_contacts_enabled() {
+                let (body_id1, body_id2) = joint.get_bodies();
+                jointed.insert((body_id1, body_id2));
+                jointed.insert((body_id2, body_id1));
+            }
+        }
+
+        self.solver.clear();
+        self.sensor_overlaps.clear();
+        let mut islands = Islands::new(self.bodies.capacity());
+        let mut contacts = HashMap::new();
+        let mut asleep_pairs = Vec::new();
+        let mut woken = Vec::new();
+        let mut manifold = Vec::new();
+        for (body_id1, body_id2) in pairs {
+            if !self.can_collide(body_id1, body_id2) {
+                continue;
+            }
+            let (body1, _) = &self.bodies[body_id1.inner];
+            let (body2, _) = &self.bodies[body_id2.inner];
+            if body1.is_sensor() || body2.is_sensor() {
+                // sensors only look for overlaps, even with sleeping or jointed bodies.
+                let moving = body1.get_body_type() != BodyType::Static
+                    || body2.get_body_type() != BodyType::Static;
+                let pos12 = body1.get_position().inverse() * body2.get_position();
+                if moving && body1.get_shape().intersect(body2.get_shape(), &pos12) {
+                    self.sensor_overlaps.push((body_id1, body_id2));
+                }
+                continue;
+            }
+            if jointed.contains(&(body_id1, body_id2)) {
+                continue;
+            }
+            let dynamic = body1.get_inv_mass() != 0.0 && body2.get_inv_mass() != 0.0;
+            if body1.get_inv_mass() == 0.0 && body2.get_inv_mass() == 0.0 {
+                continue;
+            }
+            let (awake1, awake2) = (is_awake(body1), is_awake(body2));
+            if !awake1 && !awake2 {
+                // sleeping bodies were touching when they fell asleep, keep them together.
+                if dynamic {
+                    islands.union(body_id1.slot(), body_id2.slot());
+                }
+                let pair = ordered_pair(body_id1, body_id2);
+                if let Some(point) = self.contact_pairs.get(&pair) {
+                    asleep_pairs.push((pair, *point));
+                }
+                continue;
+            }
+            manifold.clear();
+            body1.contact_manifold(body2, &mut manifold);
+            // static bodies don't join islands, or everything on the ground would be one.
+            if dynamic && !manifold.is_empty() {
+                islands.union(body_id1.slot(), body_id2.slot());
+            }
+            // a moving kinematic body pushes sleeping bodies.
+            if !manifold.is_empty() && body1.is_kinematic_moving() && body2.is_sleeping() {
+                woken.push(body_id2);
+            }
+            if !manifold.is_empty() && body2.is_kinematic_moving() && body1.is_sleeping() {
+                woken.push(body_id1);
+            }
+            for contact in &manifold {
+                self.solver
+                    .add_contact((body_id1, body1), (body_id2, body2), contact, delta_time);
+            }
+            if let Some(contact) = pair_contact(body1, body2, &manifold) {
+                contacts.insert(ordered_pair(body_id1, body_id2), contact);
+            }
+        }
+        for (joint_id, joint) in &self.joints {
+            let (body_id1, body_id2) = joint.get_bodies();
+            let (body1, _) = &self.bodies[body_id1.inner];
+            let (body2, _) = &self.bodies[body_id2.inner];
+            let dynamic = body1.get_inv_mass() != 0.0 && body2.get_inv_mass() != 0.0;
+            if dynamic {
+                islands.union(body_id1.slot(), body_id2.slot());
+            }
+            let (awake1, awake2) = (is_awake(body1), is_awake(body2));
+            if body1.is_kinematic_moving() && body2.is_sleeping() {
+                woken.push(body_id2);
+            }
+            if body2.is_kinematic_moving() && body1.is_sleeping() {
+                woken.push(body_id1);
+            }
+            if awake1 || awake2 {
+                let joint_id = JointId::new(joint_id);
+                let (body1, body2) = ((body_id1, body1), (body_id2, body2));
+                self.solver
+                    .add_joint(joint_id, joint, body1, body2, delta_time);
+            }
+        }
+        for (_, constraint) in &self.distance_constraints {
+            if let (body_id1, Some(body_id2)) = constraint.get_bodies() {
+                let (body1, _) = &self.bodies[body_id1.inner];
+                let (body2, _) = &self.bodies[body_id2.inner];
+                if body1.get_inv_mass() != 0.0 && body2.get_inv_mass() != 0.0 {
+                    islands.union(body_id1.slot(), body_id2.slot());
                 }
+            }
+        }
+        for body_id in woken {
+            self.bodies[body_id.inner].0.wake();
+        }
+        self.wake_islands(&mut islands);
+        self.solver.solve();
+        for (body_id1, body_id2, impulse) in self.solver.contact_impulses() {
+            if let Some(contact) = contacts.get_mut(&ordered_pair(body_id1, body_id2)) {
+                contact.impulse += impulse;
+            }
+        }
+
+        let mut ccd_bodies = Vec::new();
+        for (body_id, (body, bounding_id)) in &mut self.bodies {
+            if body.is_sleeping() {
+                continue;
+            }
+            let body_id = RigidBodyId::new(body_id);
+            let pseudo_velocity = self.solver.write_back(body_id, body);
+            let mut moved = false;
+            if body.get_ccd() && body.get_inv_mass() != 0.0 && !body.is_sensor() {
+                ccd_bodies.push(body_id);
             } else {
-                panic!();
+                moved |= body.integrate_position(delta_time);
+            }
+            if let Some((linear, angular)) = pseudo_velocity {
+                moved |= body.displace(&(linear * delta_time), &(angular * delta_time));
+            }
+            if moved {
+                if let Some(bounding_id) = bounding_id {
+                    self.bvh.remove(*bounding_id);
+                    *bounding_id = self.bvh.insert(body_id, contact_aabb(body));
+                }
+            }
+        }
+
+        // other bodies have moved already, ccd bodies are swept against their new positions.
+        for body_id in ccd_bodies {
+            self.integrate_ccd(body_id, delta_time, &mut contacts);
+            let (body, bounding_id) = &mut self.bodies[body_id.inner];
+            if let Some(bounding_id) = bounding_id {
+                self.bvh.remove(*bounding_id);
+                *bounding_id = self.bvh.insert(body_id, contact_aabb(body));
+            }
+        }
+        self.sleep_islands(&mut islands, delta_time);
+        self.update_collision_events(contacts, asleep_pairs);
+    }
+
+    /// Compares the pairs in contact with those of the previous update and adds the events.
+    /// Pairs of sleeping bodies stay in contact without events.
+    fn update_collision_events(
+        &mut self,
+        contacts: HashMap<(RigidBodyId, RigidBodyId), PairContact>,
+        asleep_pairs: Vec<((RigidBodyId, RigidBodyId), Point3)>,
+    ) {
+        let mut contact_pairs: HashMap<_, _> = asleep_pairs.into_iter().collect();
+        for ((body_a, body_b), contact) in contacts {
+            if !contact.touching && contact.impulse <= 0.0 {
+                continue;
+            }
+            let kind = if self.contact_pairs.contains_key(&(body_a, body_b)) {
+                CollisionEventKind::Persisted
+            } else {
+                CollisionEventKind::Started
+            };
+            self.collision_events.push(CollisionEvent {
+                kind,
+                body_a,
+                body_b,
+                impulse: contact.impulse,
+                point: contact.point,
+            });
+            contact_pairs.insert((body_a, body_b), contact.point);
+        }
+        for ((body_a, body_b), point) in &self.contact_pairs {
+            if !contact_pairs.contains_key(&(*body_a, *body_b)) {
+                self.collision_events.push(CollisionEvent {
+                    kind: CollisionEventKind::Ended,
+                    body_a: *body_a,
+                    body_b: *body_b,
+                    impulse: 0.0,
+                    point: *point,
+                });
+            }
+        }
+        self.contact_pairs = contact_pairs;
+    }
+
+    /// Applies the springs of the distance constraints which have an awake body.
+    fn apply_distance_constraints(&mut self, delta_time: Float) {
+        let awake = |body: &RigidBody| body.get_inv_mass() != 0.0 && !body.is_sleeping();
+        for (_, constraint) in &self.distance_constraints {
+            match constraint.get_bodies() {
+                (body_id1, Some(body_id2)) => {
+                    let (body1, body2) = self.bodies.get2_mut(body_id1.inner, body_id2.inner);
+                    let (body1, body2) = (&mut body1.unwrap().0, &mut body2.unwrap().0);
+                    if awake(body1) || awake(body2) {
+                        constraint.apply(body1, Some(body2), delta_time);
+                    }
+                }
+                (body_id1, None) => {
+                    let body1 = &mut self.bodies[body_id1.inner].0;
+                    if awake(body1) {
+                        constraint.apply(body1, None, delta_time);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Wakes every island that has an awake body, since it may push the sleeping ones.
+    fn wake_islands(&mut self, islands: &mut Islands) {
+        let mut awake = vec![false; self.bodies.capacity()];
+        for (body_id, (body, _)) in &self.bodies {
+            if body.get_inv_mass() != 0.0 && !body.is_sleeping() {
+                awake[islands.find(RigidBodyId::new(body_id).slot())] = true;
+            }
+        }
+        for (body_id, (body, _)) in &mut self.bodies {
+            if body.is_sleeping() && awake[islands.find(RigidBodyId::new(body_id).slot())] {
+                body.wake();
+            }
+        }
+    }
+
+    /// Puts an island to sleep once all of its bodies have been slow for `TIME_TO_SLEEP`.
+    fn sleep_islands(&mut self, islands: &mut Islands, delta_time: Float) {
+        let mut sleep_time = vec![Float::INFINITY; self.bodies.capacity()];
+        for (body_id, (body, _)) in &mut self.bodies {
+            if body.is_dynamic() && !body.is_sleeping() {
+                let island = islands.find(RigidBodyId::new(body_id).slot());
+                sleep_time[island] = sleep_time[island].min(body.update_sleep_time(delta_time));
+            }
+        }
+        for (body_id, (body, _)) in &mut self.bodies {
+            let island = islands.find(RigidBodyId::new(body_id).slot());
+            if body.is_dynamic() && sleep_time[island] >= TIME_TO_SLEEP {
+                body.sleep();
+            }
+        }
+    }
+
+    /// Returns true if the bodies' collision layers match and the pair isn't ignored.
+    fn can_collide(&self, body_id1: RigidBodyId, body_id2: RigidBodyId) -> bool {
+        let (body1, _) = &self.bodies[body_id1.inner];
+        let (body2, _) = &self.bodies[body_id2.inner];
+        body1.can_collide_with(body2) && !self.ignored_pairs.contains(&(body_id1, body_id2))
+    }
+
+    /// Moves a ccd body through the step, stopping at each impact to resolve it.
+    /// If the body is still hitting things after `MAX_CCD_SUBSTEPS` impacts,
+    /// it loses the rest of the step rather than passing through.
+    /// The impacts are added to the contacts of the update.
+    fn integrate_ccd(
+        &mut self,
+        body_id: RigidBodyId,
+        delta_time: Float,
+        contacts: &mut HashMap<(RigidBodyId, RigidBodyId), PairContact>,
+    ) {
+        let mut remaining = delta_time;
+        for _ in 0..MAX_CCD_SUBSTEPS {
+            let body = &self.bodies[body_id.inner].0;
+            let motion = Motion::new(body);
+            let mut swept = contact_aabb(body);
+            swept = swept.merge(&body.get_shape().build_aabb(&motion.position_at(remaining)));
+            let mut candidates = Vec::new();
+            self.bvh.query(&swept, &mut candidates);
+            let candidates = candidates
+                .into_iter()
+                .copied()
+                .chain(self.planes.iter().copied());
+
+            let mut first_impact: Option<(Float, RigidBodyId)> = None;
+            let candidates = candidates.filter(|id| {
+                *id != body_id
+                    && self.can_collide(body_id, *id)
+                    && !self.bodies[id.inner].0.is_sensor()
+            });
+            for other_id in candidates {
+                let other = &self.bodies[other_id.inner].0;
+                let toi = time_of_impact(
+                    body.get_shape(),
+                    &motion,
+                    other.get_shape(),
+                    &Motion::new_static(*other.get_position()),
+                    remaining,
+                    CONTACT_MARGIN * 0.5,
+                );
+                if let Some(toi) = toi {
+                    if first_impact.is_none_or(|(first, _)| toi < first) {
+                        first_impact = Some((toi, other_id));
+                    }
+                }
+            }
+
+            let Some((toi, other_id)) = first_impact else {
+                self.bodies[body_id.inner].0.integrate_position(remaining);
+                return;
+            };
+            self.bodies[body_id.inner].0.integrate_position(toi);
+            remaining -= toi;
+
+            // resolve the impact alone, then carry on with the new velocities.
+            let (body, _) = &self.bodies[body_id.inner];
+            let (other, _) = &self.bodies[other_id.inner];
+            let mut manifold = Vec::new();
+            body.contact_manifold(other, &mut manifold);
+            if manifold.is_empty() {
+                // most shapes only report contacts once they touch.
+                let pos12 = body.get_position().inverse() * other.get_position();
+                let shape = body.get_shape();
+                manifold.extend(shape.closest_points(other.get_shape(), &pos12, CONTACT_MARGIN));
+            }
+            let mut solver = ContactSolver::new();
+            for contact in &mut manifold {
+                contact.toi = 1.0 - remaining / delta_time;
+                solver.add_contact((body_id, body), (other_id, other), contact, remaining);
+            }
+            solver.solve();
+            if let Some(mut contact) = pair_contact(body, other, &manifold) {
+                contact.impulse = solver
+                    .contact_impulses()
+                    .map(|(_, _, impulse)| impulse)
+                    .sum();
+                let pair = ordered_pair(body_id, other_id);
+                contacts
+                    .entry(pair)
+                    .and_modify(|c| {
+                        c.impulse += contact.impulse;
+                        c.touching |= contact.touching;
+                    })
+                    .or_insert(contact);
+            }
+            solver.write_back(body_id, &mut self.bodies[body_id.inner].0);
+            solver.write_back(other_id, &mut self.bodies[other_id.inner].0);
+        }
+    }
+
+    pub fn get_fixed_delta_time(&self) -> Float {
+        self.fixed_delta_time
+    }
+    pub fn set_fixed_delta_time(&mut self, fixed_delta_time: Float) {
+        assert!(fixed_delta_time > 0.0, "fixed_delta_time must be positive");
+        self.fixed_delta_time = fixed_delta_time;
+    }
+    pub fn get_max_substeps(&self) -> usize {
+        self.max_substeps
+    }
+    pub fn set_max_substeps(&mut self, max_substeps: usize) {
+        self.max_substeps = max_substeps;
+    }
+
+    /// Returns the first body hit by the ray within max_toi which passes the filter.
+    pub fn cast_ray(
+        &self,
+        origin: &Point3,
+        dir: &UnitVector3,
+        max_toi: Float,
+        filter: impl Fn(RigidBodyId, &RigidBody) -> bool,
+    ) -> Option<RayHit> {
+        let mut first: Option<RayHit> = None;
+        self.visit_ray_hits(origin, dir, max_toi, filter, |hit| {
+            if first.is_none_or(|first| hit.distance < first.distance) {
+                first = Some(hit);
+            }
+        });
+        first
+    }
+
+    /// Stores every body hit by the ray within max_toi which passes the filter,
+    /// in no particular order.
+    pub fn cast_ray_all<S: Storage<RayHit>>(
+        &self,
+        origin: &Point3,
+        dir: &UnitVector3,
+        max_toi: Float,
+        filter: impl Fn(RigidBodyId, &RigidBody) -> bool,
+        hits: &mut S,
+    ) {
+        self.visit_ray_hits(origin, dir, max_toi, filter, |hit| hits.store(hit));
+    }
+
+    /// Sweeps the shape from the start pose along the direction, without rotating it.
+    /// Returns the first body passing the filter it touches within max_distance.
+    /// A shape which overlaps a body at the start hits it at 0.
+    /// Bodies of any shape can be hit, but TriMesh, HeightField and Plane shapes never hit
+    /// bodies of those shapes, since they don't collide with each other.
+    pub fn cast_shape(
+        &self,
+        shape: &ShapeType,
+        start: &Isometry3,
+        direction: &UnitVector3,
+        max_distance: Float,
+        filter: impl Fn(RigidBodyId, &RigidBody) -> bool,
+    ) -> Option<ShapeHit> {
+        let mut motion = Motion::new_static(*start);
+        motion.linear_velocity = direction.into_inner();
+        let end = motion.position_at(max_distance);
+        let swept = shape.build_aabb(start).merge(&shape.build_aabb(&end));
+        let mut candidates = Vec::new();
+        self.bvh.query(&swept, &mut candidates);
+        let candidates = candidates.into_iter().chain(self.planes.iter());
+
+        let mut first: Option<(Float, RigidBodyId)> = None;
+        for id in candidates {
+            let (body, _) = &self.bodies[id.inner];
+            if !filter(*id, body) {
+                continue;
+            }
+            let pos12 = start.inverse() * body.get_position();
+            let overlapping = shape
+                .closest_points(body.get_shape(), &pos12, SHAPE_CAST_TOLERANCE)
+                .is_some();
+            let toi = if overlapping {
+                Some(0.0)
+            } else {
+                let body_motion = Motion::new_static(*body.get_position());
+                let other = body.get_shape();
+                time_of_impact(
+                    shape,
+                    &motion,
+                    other,
+                    &body_motion,
+                    max_distance,
+                    SHAPE_CAST_TOLERANCE,
+                )
+            };
+            if let Some(toi) = toi {
+                if first.is_none_or(|(first, _)| toi < first) {
+                    first = Some((toi, *id));
+                }
+            }
+        }
+
+        let (toi, id) = first?;
+        let (body, _) = &self.bodies[id.inner];
+        let position = motion.position_at(toi);
+        let pos12 = position.inverse() * body.get_position();
+        // the shapes are within the tolerance at the hit, unless the search stopped just short
+        // of it or the penetration is degenerate, so look a bit farther before giving up.
+        let contact = [2.0, 8.0, 32.0].into_iter().find_map(|scale| {
+            shape.closest_points(body.get_shape(), &pos12, SHAPE_CAST_TOLERANCE * scale)
+        });
+        let (point, normal) = match contact {
+            Some(contact) => (
+                body.local_to_world_point(&contact.point2),
+                -(position.rotation * contact.normal1),
+            ),
+            // the hit was found, so report it at the front of the shape, facing the cast.
+            None => {
+                let local_dir = position.inverse_transform_unit_vector(direction);
+                let front = shape.as_shape().supporting_point(&local_dir, 0.0);
+                (position.transform_point(&front), -*direction)
+            }
+        };
+        Some(ShapeHit {
+            body: id,
+            toi,
+            point,
+            normal,
+        })
+    }
+
+    /// Stores every body passing the filter which overlaps the box.
+    pub fn query_aabb<S: Storage<RigidBodyId>>(
+        &self,
+        aabb: &AABB,
+        filter: impl Fn(RigidBodyId, &RigidBody) -> bool,
+        result: &mut S,
+    ) {
+        let shape = ShapeType::Cube(Cube::new(aabb.radii));
+        let pose = Isometry3::translation(aabb.center.x, aabb.center.y, aabb.center.z);
+        self.query_shape(&shape, &pose, filter, result);
+    }
+
+    /// Stores every body passing the filter which overlaps the sphere.
+    pub fn query_sphere<S: Storage<RigidBodyId>>(
+        &self,
+        center: &Point3,
+        radius: Float,
+        filter: impl Fn(RigidBodyId, &RigidBody) -> bool,
+        result: &mut S,
+    ) {
+        let shape = ShapeType::Sphere(Sphere::new(radius));
+        let pose = Isometry3::translation(center.x, center.y, center.z);
+        self.query_shape(&shape, &pose, filter, result);
+    }
+
+    /// Stores every body passing the filter which overlaps the shape at the pose.
+    pub fn query_shape<S: Storage<RigidBodyId>>(
+        &self,
+        shape: &ShapeType,
+        pose: &Isometry3,
+        filter: impl Fn(RigidBodyId, &RigidBody) -> bool,
+        result: &mut S,
+    ) {
+        let mut candidates = Vec::new();
+        self.bvh.query(&shape.build_aabb(pose), &mut candidates);
+        for id in candidates.into_iter().chain(self.planes.iter()) {
+            let (body, _) = &self.bodies[id.inner];
+            let pos12 = pose.inverse() * body.get_position();
+            if filter(*id, body) && shape.intersect(body.get_shape(), &pos12) {
+                result.store(*id);
+            }
+        }
+    }
+
+    /// Stores every body passing the filter which contains the point.
+    pub fn contains_point<S: Storage<RigidBodyId>>(
+        &self,
+        point: &Point3,
+        filter: impl Fn(RigidBodyId, &RigidBody) -> bool,
+        result: &mut S,
+    ) {
+        let mut candidates = Vec::new();
+        self.bvh
+            .query(&AABB::new(*point, Vector3::zeros()), &mut candidates);
+        for id in candidates.into_iter().chain(self.planes.iter()) {
+            let (body, _) = &self.bodies[id.inner];
+            let local = body.world_to_local_point(point);
+            if filter(*id, body) && body.get_shape().contains_point(&local) {
+                result.store(*id);
+            }
+        }
+    }
+
+    fn visit_ray_hits(
+        &self,
+        origin: &Point3,
+        dir: &UnitVector3,
+        max_toi: Float,
+        filter: impl Fn(RigidBodyId, &RigidBody) -> bool,
+        mut visit: impl FnMut(RayHit),
+    ) {
+        let mut candidates = Vec::new();
+        self.bvh.query_ray(origin, dir, max_toi, &mut candidates);
+        let candidates = candidates.into_iter().chain(self.planes.iter());
+        for id in candidates {
+            let (body, _) = &self.bodies[id.inner];
+            if !filter(*id, body) {
+                continue;
+            }
+            let position = body.get_position();
+            let local_origin = position.inverse_transform_point(origin);
+            let local_dir = position.inverse_transform_unit_vector(dir);
+            let shape = body.get_shape();
+            if let Some((distance, normal)) = shape.cast_ray(&local_origin, &local_dir, max_toi) {
+                visit(RayHit {
+                    body: *id,
+                    point: origin + dir.into_inner() * distance,
+                    normal: position.rotation * normal,
+                    distance,
+                });
             }
         }
     }
 
+    /// Returns the pairs of overlapping bodies found in the last update
+    /// where at least one is a sensor.
+    pub fn get_sensor_overlaps(&self) -> &[(RigidBodyId, RigidBodyId)] {
+        &self.sensor_overlaps
+    }
+
+    /// Removes and returns the collision events of the last step,
+    /// or of the updates since the last drain.
+    pub fn drain_collision_events(&mut self) -> std::vec::Drain<'_, CollisionEvent> {
+        self.collision_events.drain(..)
+    }
+
+    pub fn get_solver(&self) -> &ContactSolver {
+        &self.solver
+    }
+    pub fn get_solver_mut(&mut self) -> &mut ContactSolver {
+        &mut self.solver
+    }
+
     pub fn insert(&mut self, rigid_body: RigidBody) -> RigidBodyId {
-        let bounding = rigid_body.get_aabb();
-        let rigid_id = RigidBodyId::new(
-            self.bodies
-                .insert((rigid_body, (ga::Index::from_raw_parts(0, 0),))),
+        assert!(
+            rigid_body.get_inv_mass() == 0.0 || !rigid_body.get_shape().is_static_only(),
+            "TriMesh, HeightField and Plane can only be used on static bodies"
         );
+        let bounding = contact_aabb(&rigid_body);
+        let is_plane = matches!(rigid_body.get_shape(), ShapeType::Plane(_));
+        let rigid_id = RigidBodyId::new(self.bodies.insert((rigid_body, None)));
 
-        let bounding_id = self.bvh.insert(rigid_id, bounding);
-        self.bodies.get_mut(rigid_id.inner).unwrap().1 = bounding_id;
+        if is_plane {
+            self.planes.push(rigid_id);
+        } else {
+            let bounding_id = self.bvh.insert(rigid_id, bounding);
+            self.bodies.get_mut(rigid_id.inner).unwrap().1 = Some(bounding_id);
+        }
         rigid_id
     }
 
+    /// Removes the body with its joints and distance constraints.
+    /// Its contacts are kept until the next update, which reports that they ended.
     pub fn remove(&mut self, id: RigidBodyId) -> Option<RigidBody> {
-        self.bodies.remove(id.inner).map(|b| b.0)
+        let (body, bounding_id) = self.bodies.remove(id.inner)?;
+        self.joints.retain(|_, joint| {
+            let (body1, body2) = joint.get_bodies();
+            body1 != id && body2 != id
+        });
+        self.distance_constraints.retain(|_, constraint| {
+            let (body1, body2) = constraint.get_bodies();
+            body1 != id && body2 != Some(id)
+        });
+        self.ignored_pairs
+            .retain(|(body1, body2)| *body1 != id && *body2 != id);
+        match bounding_id {
+            Some(bounding_id) => {
+                self.bvh.remove(bounding_id);
+            }
+            None => self.planes.retain(|p| *p != id),
+        }
+        Some(body)
     }
 
     pub fn get(&self, id: RigidBodyId) -> Option<&RigidBody> {
@@ -93,4 +793,939 @@ impl PhysicsWorld {
     pub fn get_mut(&mut self, id: RigidBodyId) -> Option<&mut RigidBody> {
         self.bodies.get_mut(id.inner).map(|b| &mut b.0)
     }
+
+    /// Makes the bodies pass through each other, whatever their collision layers.
+    pub fn ignore_pair(&mut self, body1: RigidBodyId, body2: RigidBodyId) {
+        self.ignored_pairs.insert((body1, body2));
+        self.ignored_pairs.insert((body2, body1));
+    }
+    pub fn unignore_pair(&mut self, body1: RigidBodyId, body2: RigidBodyId) {
+        self.ignored_pairs.remove(&(body1, body2));
+        self.ignored_pairs.remove(&(body2, body1));
+    }
+    pub fn is_pair_ignored(&self, body1: RigidBodyId, body2: RigidBodyId) -> bool {
+        self.ignored_pairs.contains(&(body1, body2))
+    }
+
+    pub fn insert_joint(&mut self, joint: Joint) -> JointId {
+        let (body1, body2) = joint.get_bodies();
+        assert!(
+            self.bodies.contains(body1.inner) && self.bodies.contains(body2.inner),
+            "joint bodies must be in the world"
+        );
+        JointId::new(self.joints.insert(joint))
+    }
+
+    pub fn remove_joint(&mut self, id: JointId) -> Option<Joint> {
+        self.joints.remove(id.inner)
+    }
+
+    pub fn get_joint(&self, id: JointId) -> Option<&Joint> {
+        self.joints.get(id.inner)
+    }
+    pub fn get_joint_mut(&mut self, id: JointId) -> Option<&mut Joint> {
+        self.joints.get_mut(id.inner)
+    }
+
+    pub fn insert_distance_constraint(
+        &mut self,
+        constraint: DistanceConstraint,
+    ) -> DistanceConstraintId {
+        let (body1, body2) = constraint.get_bodies();
+        assert!(
+            self.bodies.contains(body1.inner)
+                && body2.is_none_or(|body2| self.bodies.contains(body2.inner)),
+            "constrained bodies must be in the world"
+        );
+        assert!(
+            body2 != Some(body1),
+            "a body can't be constrained to itself"
+        );
+        DistanceConstraintId::new(self.distance_constraints.insert(constraint))
+    }
+
+    pub fn remove_distance_constraint(
+        &mut self,
+        id: DistanceConstraintId,
+    ) -> Option<DistanceConstraint> {
+        self.distance_constraints.remove(id.inner)
+    }
+
+    pub fn get_distance_constraint(&self, id: DistanceConstraintId) -> Option<&DistanceConstraint> {
+        self.distance_constraints.get(id.inner)
+    }
+    pub fn get_distance_constraint_mut(
+        &mut self,
+        id: DistanceConstraintId,
+    ) -> Option<&mut DistanceConstraint> {
+        self.distance_constraints.get_mut(id.inner)
+    }
+}
+
+/// Orders the ids of a pair, so that it has the same key in both orders.
+fn ordered_pair(id1: RigidBodyId, id2: RigidBodyId) -> (RigidBodyId, RigidBodyId) {
+    if id1.inner < id2.inner {
+        (id1, id2)
+    } else {
+        (id2, id1)
+    }
+}
+
+/// Returns the average point of a manifold between two bodies, with no impulse yet.
+fn pair_contact(body1: &RigidBody, body2: &RigidBody, manifold: &[Contact]) -> Option<PairContact> {
+    if manifold.is_empty() {
+        return None;
+    }
+    let mut sum = Vector3::zeros();
+    for contact in manifold {
+        let point1 = body1.local_to_world_point(&contact.point1);
+        let point2 = body2.local_to_world_point(&contact.point2);
+        sum += (point1.coords + point2.coords) * 0.5;
+    }
+    Some(PairContact {
+        impulse: 0.0,
+        point: Point3::from(sum / manifold.len() as Float),
+        touching: manifold.iter().any(|c| c.separation_distance <= 0.0),
+    })
+}
+
+/// Returns the AABB of the body padded so that bodies closer than `CONTACT_MARGIN` overlap.
+fn contact_aabb(body: &RigidBody) -> AABB {
+    let mut aabb = body.get_aabb();
+    aabb.radii.add_scalar_mut(CONTACT_MARGIN * 0.5);
+    aabb
+}
+
+/// Returns true if the body is dynamic and awake, or kinematic and moving.
+fn is_awake(body: &RigidBody) -> bool {
+    (body.is_dynamic() && !body.is_sleeping()) || body.is_kinematic_moving()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_box_tower() {
+        let mut world = PhysicsWorld::new();
+        world.insert(RigidBody::new(
+            ShapeType::Plane(Plane::new(Vector3::y_axis(), 0.0)),
+            0.0,
+        ));
+        let ids = (0..10)
+            .map(|i| {
+                let shape = ShapeType::Cube(Cube::new(Vector3::new(0.5, 0.5, 0.5)));
+                let mut body = RigidBody::new(shape, 1.0);
+                body.set_position(&Isometry3::translation(0.0, 0.5 + i as Float, 0.0));
+                world.insert(body)
+            })
+            .collect::<Vec<_>>();
+
+        for _ in 0..600 {
+            world.update(1.0 / 60.0);
+        }
+        for (i, id) in ids.into_iter().enumerate() {
+            let body = world.get(id).unwrap();
+            let expected = Vector3::new(0.0, 0.5 + i as Float, 0.0);
+            let position = body.get_position().translation.vector;
+            assert!((position - expected).magnitude() < 0.1, "{i}: {position}");
+            assert!(body.get_linear_velocity().magnitude() < 0.05);
+            assert!(body.get_angular_velocity().magnitude() < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_ccd() {
+        let shoot = |ccd: bool| {
+            let mut world = PhysicsWorld::new();
+            let wall = ShapeType::Cube(Cube::new(Vector3::new(0.01, 1.0, 1.0)));
+            let mut wall = RigidBody::new(wall, 0.0);
+            wall.set_position(&Isometry3::translation(2.0, 0.0, 0.0));
+            world.insert(wall);
+            let mut bullet = RigidBody::new(ShapeType::Sphere(Sphere::new(0.02)), 100.0);
+            bullet.set_linear_velocity(&Vector3::new(300.0, 0.0, 0.0));
+            bullet.set_ccd(ccd);
+            let bullet = world.insert(bullet);
+            for _ in 0..10 {
+                world.update(1.0 / 165.0);
+            }
+            world.get(bullet).unwrap().get_position().translation.x
+        };
+        assert!(shoot(false) > 2.0);
+        assert!(shoot(true) < 2.0);
+    }
+
+    #[test]
+    fn test_step() {
+        // the same second at different frame rates ends at the same place.
+        let fall = |frame_rate: Float| {
+            let mut world = PhysicsWorld::new();
+            let shape = ShapeType::Sphere(Sphere::new(0.5));
+            let ball = world.insert(RigidBody::new(shape, 1.0));
+            let mut alpha = 0.0;
+            for _ in 0..frame_rate as usize {
+                alpha = world.step(1.0 / frame_rate);
+                assert!((0.0..1.0).contains(&alpha));
+            }
+            let ball = world.get(ball).unwrap();
+            ball.get_interpolated_position(alpha).translation.vector
+        };
+        let position = fall(60.0);
+        assert!((position - fall(165.0)).magnitude() < 1.0e-3);
+        assert!((position - fall(144.0)).magnitude() < 1.0e-3);
+
+        // a long frame runs at most max_substeps updates and drops the rest.
+        let mut world = PhysicsWorld::new();
+        world.set_max_substeps(2);
+        let shape = ShapeType::Sphere(Sphere::new(0.5));
+        let ball = world.insert(RigidBody::new(shape, 1.0));
+        let alpha = world.step(1.0);
+        assert!((0.0..1.0).contains(&alpha));
+        let velocity = world.get(ball).unwrap().get_linear_velocity().y;
+        assert!((velocity + 9.8 * 2.0 / 60.0).abs() < 1.0e-2);
+    }
+
+    #[test]
+    fn test_sleep() {
+        let mut world = PhysicsWorld::new();
+        world.insert(RigidBody::new(
+            ShapeType::Plane(Plane::new(Vector3::y_axis(), 0.0)),
+            0.0,
+        ));
+        let cube = || ShapeType::Cube(Cube::new(Vector3::new(0.5, 0.5, 0.5)));
+        let stack = (0..2)
+            .map(|i| {
+                let mut body = RigidBody::new(cube(), 1.0);
+                body.set_position(&Isometry3::translation(0.0, 0.5 + i as Float, 0.0));
+                world.insert(body)
+            })
+            .collect::<Vec<_>>();
+        let mut aside = RigidBody::new(cube(), 1.0);
+        aside.set_position(&Isometry3::translation(5.0, 0.5, 0.0));
+        let aside = world.insert(aside);
+
+        for _ in 0..120 {
+            world.update(1.0 / 60.0);
+        }
+        for id in stack.iter().chain([&aside]) {
+            assert!(world.get(*id).unwrap().is_sleeping());
+        }
+        // sleeping bodies stay put.
+        let position = *world.get(stack[1]).unwrap().get_position();
+        world.update(1.0 / 60.0);
+        assert_eq!(*world.get(stack[1]).unwrap().get_position(), position);
+
+        // a falling cube wakes the whole stack it lands on, but not the other cube.
+        let mut falling = RigidBody::new(cube(), 1.0);
+        falling.set_position(&Isometry3::translation(0.0, 2.6, 0.0));
+        world.insert(falling);
+        for _ in 0..10 {
+            world.update(1.0 / 60.0);
+        }
+        for id in &stack {
+            assert!(!world.get(*id).unwrap().is_sleeping());
+        }
+        assert!(world.get(aside).unwrap().is_sleeping());
+
+        // pushing a sleeping body wakes it.
+        let body = world.get_mut(aside).unwrap();
+        body.apply_impulse_world(&Vector3::new(1.0, 0.0, 0.0));
+        assert!(!body.is_sleeping());
+    }
+
+    #[test]
+    fn test_joints() {
+        let mut world = PhysicsWorld::new();
+        let ball = || ShapeType::Sphere(Sphere::new(0.1));
+        let cube = || ShapeType::Cube(Cube::new(Vector3::new(0.5, 0.5, 0.5)));
+        let insert = |world: &mut PhysicsWorld, shape, inv_mass, position: Vector3| {
+            let mut body = RigidBody::new(shape, inv_mass);
+            body.set_position(&Isometry3::translation(position.x, position.y, position.z));
+            world.insert(body)
+        };
+        let connect = |world: &mut PhysicsWorld, joint_type, id1, id2, frame: Isometry3| {
+            let (b1, b2) = (world.get(id1).unwrap(), world.get(id2).unwrap());
+            world.insert_joint(Joint::new_world(joint_type, (id1, b1), (id2, b2), &frame))
+        };
+
+        // a chain of balls swinging from a fixed point.
+        let mut chain = vec![insert(
+            &mut world,
+            ball(),
+            0.0,
+            Vector3::new(0.0, 10.0, 0.0),
+        )];
+        for i in 1..4 {
+            let id = insert(&mut world, ball(), 1.0, Vector3::new(i as Float, 10.0, 0.0));
+            let frame = Isometry3::translation(i as Float - 1.0, 10.0, 0.0);
+            connect(&mut world, JointType::Spherical, chain[i - 1], id, frame);
+            chain.push(id);
+        }
+
+        // a door swinging around its hinge, stopped by the limits.
+        let post = insert(&mut world, cube(), 0.0, Vector3::new(10.0, 0.5, 0.0));
+        let door = insert(&mut world, cube(), 1.0, Vector3::new(11.0, 0.5, 0.0));
+        let hinge = Joint::axis_frame(&Point3::new(10.5, 0.5, 0.0), &Vector3::y_axis());
+        let limits = Some((-0.5, 0.5));
+        let door_hinge = JointType::Revolute {
+            limits,
+            motor: None,
+        };
+        let door_joint = connect(&mut world, door_hinge, post, door, hinge);
+        world
+            .get_mut(door)
+            .unwrap()
+            .set_angular_velocity(&Vector3::new(0.0, 5.0, 0.0));
+
+        // a wheel driven by a motor.
+        let axle = insert(&mut world, cube(), 0.0, Vector3::new(20.0, 5.0, 0.0));
+        let wheel = insert(&mut world, ball(), 1.0, Vector3::new(20.0, 5.0, 1.0));
+        let axis = Joint::axis_frame(&Point3::new(20.0, 5.0, 1.0), &Vector3::z_axis());
+        let motor = Some(JointMotor {
+            target_velocity: 2.0,
+            max_torque: 10.0,
+        });
+        let motor_hinge = JointType::Revolute {
+            limits: None,
+            motor,
+        };
+        connect(&mut world, motor_hinge, axle, wheel, axis);
+
+        // a box sliding on a rail until the end of it.
+        let rail = insert(&mut world, cube(), 0.0, Vector3::new(30.0, 5.0, 0.0));
+        let slider = insert(&mut world, cube(), 1.0, Vector3::new(30.0, 5.0, 2.0));
+        let frame = Joint::axis_frame(&Point3::new(30.0, 5.0, 2.0), &Vector3::x_axis());
+        let prismatic = JointType::Prismatic {
+            limits: Some((0.0, 1.0)),
+        };
+        let slider_joint = connect(&mut world, prismatic, rail, slider, frame);
+        world
+            .get_mut(slider)
+            .unwrap()
+            .set_linear_velocity(&Vector3::new(5.0, 0.0, 0.0));
+
+        // a box welded to the side of another.
+        let wall = insert(&mut world, cube(), 0.0, Vector3::new(40.0, 5.0, 0.0));
+        let shelf = insert(&mut world, cube(), 1.0, Vector3::new(41.0, 5.0, 0.0));
+        let frame = Isometry3::translation(40.5, 5.0, 0.0);
+        connect(&mut world, JointType::Weld, wall, shelf, frame);
+
+        let position = |world: &PhysicsWorld, id| {
+            let body: &RigidBody = world.get(id).unwrap();
+            body.get_position().translation.vector
+        };
+        let mut lowest: Float = 10.0;
+        for _ in 0..120 {
+            world.update(1.0 / 60.0);
+            lowest = lowest.min(position(&world, chain[3]).y);
+            let (b1, b2) = (world.get(post).unwrap(), world.get(door).unwrap());
+            let angle = world.get_joint(door_joint).unwrap().angle(b1, b2);
+            assert!(angle.abs() < 0.5 + 0.05, "{angle}");
+        }
+
+        for pair in chain.windows(2) {
+            let distance = (position(&world, pair[1]) - position(&world, pair[0])).magnitude();
+            assert!((distance - 1.0).abs() < 0.05, "{distance}");
+        }
+        // the chain has swung down.
+        assert!(lowest < 8.0);
+
+        let hinge = Vector3::new(10.5, 0.5, 0.0);
+        assert!(((position(&world, door) - hinge).magnitude() - 0.5).abs() < 0.05);
+
+        let velocity = world.get(wheel).unwrap().get_angular_velocity();
+        assert!((velocity - Vector3::new(0.0, 0.0, 2.0)).magnitude() < 0.1);
+        assert!((position(&world, wheel) - Vector3::new(20.0, 5.0, 1.0)).magnitude() < 0.05);
+
+        let (b1, b2) = (world.get(rail).unwrap(), world.get(slider).unwrap());
+        let translation = world.get_joint(slider_joint).unwrap().translation(b1, b2);
+        assert!((translation - 1.0).abs() < 0.05, "{translation}");
+        let slider = position(&world, slider);
+        assert!((slider.y - 5.0).abs() < 0.05 && (slider.z - 2.0).abs() < 0.05);
+
+        assert!((position(&world, shelf) - Vector3::new(41.0, 5.0, 0.0)).magnitude() < 0.05);
+        assert!(world.get(shelf).unwrap().get_position().rotation.angle() < 0.05);
+    }
+
+    #[test]
+    fn test_distance_constraints() {
+        let mut world = PhysicsWorld::new();
+        let ball = |x: Float, y: Float| {
+            let mut body = RigidBody::new(ShapeType::Sphere(Sphere::new(0.1)), 1.0);
+            body.set_position(&Isometry3::translation(x, y, 0.0));
+            body
+        };
+        let center = Point3::origin();
+
+        // a stiff rope of balls hanging from a point, each segment at most 0.5 long.
+        let stiff = |mut segment: DistanceConstraint| {
+            segment.set_stiffness(1.0e5);
+            segment.set_damping(500.0);
+            segment
+        };
+        let top = Point3::new(0.0, 10.0, 0.0);
+        let mut rope = vec![world.insert(ball(0.5, 10.0))];
+        let segment = DistanceConstraint::new_world((rope[0], center), top, 0.0, 0.5);
+        world.insert_distance_constraint(stiff(segment));
+        for i in 1..5 {
+            let id = world.insert(ball(0.5 * (i + 1) as Float, 10.0));
+            let segment = DistanceConstraint::new((rope[i - 1], center), (id, center), 0.0, 0.5);
+            world.insert_distance_constraint(stiff(segment));
+            rope.push(id);
+        }
+
+        // a bungee which starts slack and stretches.
+        let bungee = world.insert(ball(10.0, 10.0));
+        let mut cord =
+            DistanceConstraint::new_world((bungee, center), Point3::new(10.0, 10.0, 0.0), 0.0, 1.0);
+        cord.set_stiffness(50.0);
+        cord.set_damping(2.0);
+        world.insert_distance_constraint(cord);
+
+        let mut lowest: Float = 10.0;
+        for _ in 0..300 {
+            world.update(1.0 / 60.0);
+            let y = world.get(bungee).unwrap().get_position().translation.y;
+            lowest = lowest.min(y);
+        }
+
+        let position = |id| world.get(id).unwrap().get_position().translation.vector;
+        let mut previous = top.coords;
+        for id in &rope {
+            let length = (position(*id) - previous).magnitude();
+            assert!(length < 0.5 + 0.02, "{length}");
+            previous = position(*id);
+        }
+        // the rope has swung down from where it started.
+        assert!(position(rope[4]).y < 9.0);
+
+        // the bungee overshoots before coming to rest at its stretched length.
+        let stretch = 9.8 / 50.0;
+        let rest = position(bungee).y;
+        assert!((rest - (9.0 - stretch)).abs() < 0.05, "{rest}");
+        assert!(lowest < rest - 0.1);
+    }
+
+    #[test]
+    fn test_cast_ray() {
+        let mut world = PhysicsWorld::new();
+        let ground = world.insert(RigidBody::new(
+            ShapeType::Plane(Plane::new(Vector3::y_axis(), 0.0)),
+            0.0,
+        ));
+        let targets = (0..3)
+            .map(|i| {
+                let shape = if i % 2 == 0 {
+                    ShapeType::Sphere(Sphere::new(0.5))
+                } else {
+                    ShapeType::Cube(Cube::new(Vector3::new(0.5, 0.5, 0.5)))
+                };
+                let mut body = RigidBody::new(shape, 1.0);
+                let rotation = Vector3::new(0.0, PI / 2.0, 0.0);
+                body.set_position(&Isometry3::new(
+                    Vector3::new(i as Float * 3.0, 1.0, 0.0),
+                    rotation,
+                ));
+                world.insert(body)
+            })
+            .collect::<Vec<_>>();
+
+        let origin = Point3::new(-5.0, 1.0, 0.0);
+        let dir = Vector3::x_axis();
+        let hit = world.cast_ray(&origin, &dir, 100.0, |_, _| true).unwrap();
+        assert_eq!(hit.body, targets[0]);
+        assert!((hit.distance - 4.5).abs() < 1.0e-4);
+        assert!((hit.point - Point3::new(-0.5, 1.0, 0.0)).magnitude() < 1.0e-4);
+        assert!((hit.normal.into_inner() + dir.into_inner()).magnitude() < 1.0e-4);
+
+        // the filter skips the sphere, the rotated cube behind it is hit on its face.
+        let skip_first = |id: RigidBodyId, _: &RigidBody| id != targets[0];
+        let hit = world.cast_ray(&origin, &dir, 100.0, skip_first).unwrap();
+        assert_eq!(hit.body, targets[1]);
+        assert!((hit.distance - 7.5).abs() < 1.0e-4);
+        assert!((hit.normal.into_inner() + dir.into_inner()).magnitude() < 1.0e-4);
+
+        assert_eq!(world.cast_ray(&origin, &dir, 4.0, |_, _| true), None);
+        let mut hits = Vec::new();
+        world.cast_ray_all(&origin, &dir, 100.0, |_, _| true, &mut hits);
+        assert_eq!(hits.len(), 3);
+
+        // the ground is hit too, even though it isn't in the BVH.
+        let down = UnitVector3::new_normalize(Vector3::new(1.0, -1.0, 0.0));
+        let hit = world.cast_ray(&Point3::new(-5.0, 2.0, 0.0), &down, 100.0, |_, _| true);
+        assert_eq!(hit.unwrap().body, ground);
+    }
+
+    #[test]
+    fn test_cast_shape() {
+        let mut world = PhysicsWorld::new();
+        world.insert(RigidBody::new(
+            ShapeType::Plane(Plane::new(Vector3::y_axis(), 0.0)),
+            0.0,
+        ));
+        let mut wall = RigidBody::new(ShapeType::Cube(Cube::new(Vector3::new(0.5, 2.0, 2.0))), 0.0);
+        wall.set_position(&Isometry3::translation(5.0, 2.0, 0.0));
+        let wall = world.insert(wall);
+
+        // a ball thrown at the wall.
+        let ball = ShapeType::Sphere(Sphere::new(0.5));
+        let start = Isometry3::translation(0.0, 1.0, 0.0);
+        let dir = Vector3::x_axis();
+        let hit = world
+            .cast_shape(&ball, &start, &dir, 10.0, |_, _| true)
+            .unwrap();
+        assert_eq!(hit.body, wall);
+        assert!((hit.toi - 4.0).abs() < 2.0e-3, "{}", hit.toi);
+        assert!((hit.point - Point3::new(4.5, 1.0, 0.0)).magnitude() < 2.0e-3);
+        assert!((hit.normal.into_inner() + dir.into_inner()).magnitude() < 1.0e-3);
+        assert_eq!(
+            world.cast_shape(&ball, &start, &dir, 3.0, |_, _| true),
+            None
+        );
+
+        // a box dropped on the ground.
+        let cube = ShapeType::Cube(Cube::new(Vector3::new(0.5, 0.5, 0.5)));
+        let start = Isometry3::translation(-5.0, 3.0, 0.0);
+        let hit = world
+            .cast_shape(&cube, &start, &(-Vector3::y_axis()), 10.0, |_, _| true)
+            .unwrap();
+        assert!((hit.toi - 2.5).abs() < 2.0e-3);
+        assert_eq!(hit.normal, Vector3::y_axis());
+
+        // starting in the wall hits it at once, unless it's filtered out.
+        let inside = Isometry3::translation(5.0, 3.0, 0.0);
+        let hit = world
+            .cast_shape(&ball, &inside, &dir, 10.0, |_, _| true)
+            .unwrap();
+        assert_eq!((hit.body, hit.toi), (wall, 0.0));
+        let not_wall = |id: RigidBodyId, _: &RigidBody| id != wall;
+        assert_eq!(world.cast_shape(&ball, &inside, &dir, 10.0, not_wall), None);
+
+        // meshes and height fields are hit like any other body.
+        let vertices = vec![
+            Point3::new(-1.0, 0.0, -1.0),
+            Point3::new(1.0, 0.0, -1.0),
+            Point3::new(1.0, 0.0, 1.0),
+            Point3::new(-1.0, 0.0, 1.0),
+        ];
+        let mut mesh = RigidBody::new(
+            ShapeType::TriMesh(TriMesh::new(vertices, &[0, 2, 1, 0, 3, 2])),
+            0.0,
+        );
+        mesh.set_position(&Isometry3::translation(0.0, 1.0, 10.0));
+        let mesh = world.insert(mesh);
+        let heights = vec![0.0; 9];
+        let field = HeightField::new(heights, 3, 3, Vector3::new(2.0, 1.0, 2.0));
+        let mut field = RigidBody::new(ShapeType::HeightField(field), 0.0);
+        field.set_position(&Isometry3::translation(0.0, 2.0, 20.0));
+        let field = world.insert(field);
+        let down = -Vector3::y_axis();
+        for (body, z, height) in [(mesh, 10.0, 1.0), (field, 20.0, 2.0)] {
+            let start = Isometry3::translation(0.0, 4.0, z);
+            let hit = world
+                .cast_shape(&ball, &start, &down, 10.0, |_, _| true)
+                .unwrap();
+            assert_eq!(hit.body, body);
+            assert!((hit.toi - (3.5 - height)).abs() < 2.0e-3, "{}", hit.toi);
+            assert!((hit.normal.into_inner() - Vector3::y()).magnitude() < 1.0e-3);
+        }
+
+        // but casting a mesh doesn't hit the ground.
+        let mesh = world.get(mesh).unwrap().get_shape().clone();
+        let start = Isometry3::translation(-5.0, 1.0, -10.0);
+        assert_eq!(
+            world.cast_shape(&mesh, &start, &down, 10.0, |_, _| true),
+            None
+        );
+    }
+
+    #[test]
+    fn test_overlap_queries() {
+        let mut world = PhysicsWorld::new();
+        let ground = world.insert(RigidBody::new(
+            ShapeType::Plane(Plane::new(Vector3::y_axis(), 0.0)),
+            0.0,
+        ));
+        let bodies = (0..5)
+            .map(|i| {
+                let shape = if i % 2 == 0 {
+                    ShapeType::Sphere(Sphere::new(0.5))
+                } else {
+                    ShapeType::Cube(Cube::new(Vector3::new(0.5, 0.5, 0.5)))
+                };
+                let mut body = RigidBody::new(shape, 1.0);
+                body.set_position(&Isometry3::translation(i as Float * 2.0, 0.5, 0.0));
+                world.insert(body)
+            })
+            .collect::<Vec<_>>();
+        let sorted = |mut ids: Vec<RigidBodyId>| {
+            ids.sort_by_key(|id| id.slot());
+            ids
+        };
+        let all = |_: RigidBodyId, _: &RigidBody| true;
+
+        // an explosion between the second and third bodies, above the ground.
+        let mut result = Vec::new();
+        world.query_sphere(&Point3::new(3.0, 1.2, 0.0), 1.1, all, &mut result);
+        assert_eq!(sorted(result), vec![bodies[1], bodies[2]]);
+
+        // the corner of the box just misses the sphere, though their AABBs overlap.
+        let mut result = Vec::new();
+        let aabb = AABB::new(Point3::new(-0.9, 1.4, 0.0), Vector3::new(0.5, 0.5, 0.5));
+        world.query_aabb(&aabb, all, &mut result);
+        assert!(result.is_empty());
+
+        let mut result = Vec::new();
+        let capsule = ShapeType::Capsule(Capsule::new(0.2, 2.0));
+        let pose = Isometry3::translation(6.0, 1.0, 0.0);
+        world.query_shape(&capsule, &pose, all, &mut result);
+        assert_eq!(sorted(result), vec![ground, bodies[3]]);
+
+        let mut result = Vec::new();
+        let not_ground = |id: RigidBodyId, _: &RigidBody| id != ground;
+        world.query_shape(&capsule, &pose, not_ground, &mut result);
+        assert_eq!(result, vec![bodies[3]]);
+
+        let mut result = Vec::new();
+        world.contains_point(&Point3::new(8.2, 0.6, 0.1), all, &mut result);
+        assert_eq!(result, vec![bodies[4]]);
+        let mut result = Vec::new();
+        world.contains_point(&Point3::new(5.0, -0.1, 0.0), all, &mut result);
+        assert_eq!(result, vec![ground]);
+    }
+
+    #[test]
+    fn test_collision_filtering() {
+        let mut world = PhysicsWorld::new();
+        let mut ground = RigidBody::new(ShapeType::Plane(Plane::new(Vector3::y_axis(), 0.0)), 0.0);
+        ground.set_collision_groups(LAYER_STATIC);
+        // the ground doesn't collide with enemies.
+        ground.set_collision_mask(LAYER_ALL & !LAYER_ENEMY);
+        world.insert(ground);
+        let drop = |world: &mut PhysicsWorld, x: Float, groups: u32, mask: u32| {
+            let shape = ShapeType::Cube(Cube::new(Vector3::new(0.5, 0.5, 0.5)));
+            let mut body = RigidBody::new(shape, 1.0);
+            body.set_position(&Isometry3::translation(x, 0.5, 0.0));
+            body.set_collision_groups(groups);
+            body.set_collision_mask(mask);
+            world.insert(body)
+        };
+        // a player standing on the ground.
+        let player = drop(&mut world, 0.0, LAYER_PLAYER, LAYER_ALL);
+        // the player's weapon, overlapping it, collides with everything but is ignored by it.
+        let weapon = drop(&mut world, 0.5, LAYER_PROJECTILE, LAYER_ALL);
+        world.ignore_pair(player, weapon);
+        // debris which doesn't collide with the ground falls through it.
+        let debris = drop(&mut world, 5.0, LAYER_DEBRIS, LAYER_ALL & !LAYER_STATIC);
+        // an enemy which collides with everything still falls through the ground.
+        let ghost = drop(&mut world, 10.0, LAYER_ENEMY, LAYER_ALL);
+
+        for _ in 0..60 {
+            world.update(1.0 / 60.0);
+        }
+        let height = |id| world.get(id).unwrap().get_position().translation.y;
+        assert!((height(player) - 0.5).abs() < 0.05);
+        assert!((height(weapon) - 0.5).abs() < 0.05);
+        // the weapon wasn't pushed out of the player.
+        let x = world.get(weapon).unwrap().get_position().translation.x;
+        assert!((x - 0.5).abs() < 0.05, "{x}");
+        assert!(height(debris) < -1.0);
+        assert!(height(ghost) < -1.0);
+        assert!(world.is_pair_ignored(weapon, player));
+    }
+
+    #[test]
+    fn test_sensor() {
+        let mut world = PhysicsWorld::new();
+        // a checkpoint in the air, and a ball falling through it.
+        let mut checkpoint =
+            RigidBody::new(ShapeType::Cube(Cube::new(Vector3::new(2.0, 0.5, 2.0))), 0.0);
+        checkpoint.set_position(&Isometry3::translation(0.0, 5.0, 0.0));
+        checkpoint.set_sensor(true);
+        let checkpoint = world.insert(checkpoint);
+        let mut ball = RigidBody::new(ShapeType::Sphere(Sphere::new(0.25)), 1.0);
+        ball.set_position(&Isometry3::translation(0.0, 7.0, 0.0));
+        let ball = world.insert(ball);
+        // a sensor hanging from a fixed point is still held by its joint.
+        let mut anchor = RigidBody::new(ShapeType::Sphere(Sphere::new(0.1)), 0.0);
+        anchor.set_position(&Isometry3::translation(10.0, 0.0, 0.0));
+        let anchor = world.insert(anchor);
+        let mut hanging = RigidBody::new(ShapeType::Sphere(Sphere::new(0.5)), 1.0);
+        hanging.set_position(&Isometry3::translation(10.0, -1.0, 0.0));
+        hanging.set_sensor(true);
+        let hanging = world.insert(hanging);
+        let (b1, b2) = (world.get(anchor).unwrap(), world.get(hanging).unwrap());
+        let frame = Isometry3::translation(10.0, 0.0, 0.0);
+        let joint = Joint::new_world(JointType::Spherical, (anchor, b1), (hanging, b2), &frame);
+        world.insert_joint(joint);
+        // a kinematic body, like a character, still triggers a static sensor.
+        let mut pickup = RigidBody::new(ShapeType::Sphere(Sphere::new(1.0)), 0.0);
+        pickup.set_position(&Isometry3::translation(-10.0, 0.0, 0.0));
+        pickup.set_sensor(true);
+        let pickup = world.insert(pickup);
+        let mut character = RigidBody::new(ShapeType::Sphere(Sphere::new(0.5)), 1.0);
+        character.set_position(&Isometry3::translation(-10.0, 0.5, 0.0));
+        character.set_body_type(BodyType::Kinematic);
+        let character = world.insert(character);
+        world.update(1.0 / 60.0);
+        let overlaps = world.get_sensor_overlaps();
+        assert_eq!(overlaps.len(), 1);
+        assert!(overlaps[0] == (pickup, character) || overlaps[0] == (character, pickup));
+        world.remove(character);
+
+        let mut frames_inside = 0;
+        for _ in 0..90 {
+            world.update(1.0 / 60.0);
+            let overlaps = world.get_sensor_overlaps();
+            if !overlaps.is_empty() {
+                assert_eq!(overlaps.len(), 1);
+                let pair = overlaps[0];
+                assert!(pair == (checkpoint, ball) || pair == (ball, checkpoint));
+                frames_inside += 1;
+            }
+        }
+        // the ball went through without slowing down.
+        assert!(frames_inside > 0);
+        assert!(world.get_sensor_overlaps().is_empty());
+        let ball = world.get(ball).unwrap();
+        assert!(ball.get_position().translation.y < 0.0);
+        assert!((ball.get_linear_velocity().y + 9.8 * 1.5).abs() < 0.5);
+        let hanging = world.get(hanging).unwrap();
+        assert!((hanging.get_position().translation.y + 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_collision_events() {
+        let mut world = PhysicsWorld::new();
+        let ground = world.insert(RigidBody::new(
+            ShapeType::Plane(Plane::new(Vector3::y_axis(), 0.0)),
+            0.0,
+        ));
+        let mut ball = RigidBody::new(ShapeType::Sphere(Sphere::new(0.5)), 1.0);
+        ball.set_position(&Isometry3::translation(0.0, 2.0, 0.0));
+        ball.set_elasticity(0.0);
+        let ball = world.insert(ball);
+        let pair = ordered_pair(ground, ball);
+
+        let mut events = Vec::new();
+        for _ in 0..60 {
+            world.update(1.0 / 60.0);
+            events.extend(world.drain_collision_events());
+        }
+        assert!(events.iter().all(|e| (e.body_a, e.body_b) == pair));
+        let started = &events[0];
+        assert_eq!(started.kind, CollisionEventKind::Started);
+        // the impact stops the ball falling at about 6 m/s.
+        assert!(started.impulse > 3.0);
+        assert!(started.point.y.abs() < 0.1);
+        let resting = events.last().unwrap();
+        assert_eq!(resting.kind, CollisionEventKind::Persisted);
+        assert!((resting.impulse - 9.8 / 60.0).abs() < 0.05);
+        assert!(events[1..]
+            .iter()
+            .all(|e| e.kind == CollisionEventKind::Persisted));
+
+        // lift the ball off the ground.
+        let body = world.get_mut(ball).unwrap();
+        body.set_position(&Isometry3::translation(0.0, 2.0, 0.0));
+        world.update(1.0 / 60.0);
+        let events: Vec<_> = world.drain_collision_events().collect();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, CollisionEventKind::Ended);
+        assert_eq!(events[0].impulse, 0.0);
+        world.update(1.0 / 60.0);
+        assert_eq!(world.drain_collision_events().count(), 0);
+
+        // a body removed while touching ends its contacts.
+        for _ in 0..60 {
+            world.update(1.0 / 60.0);
+        }
+        world.drain_collision_events().for_each(drop);
+        world.remove(ball);
+        world.step(1.0 / 60.0);
+        let events: Vec<_> = world.drain_collision_events().collect();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, CollisionEventKind::Ended);
+        assert_eq!((events[0].body_a, events[0].body_b), pair);
+        world.step(1.0 / 60.0);
+        assert_eq!(world.drain_collision_events().count(), 0);
+    }
+
+    #[test]
+    fn test_kinematic() {
+        let mut world = PhysicsWorld::new();
+        world.insert(RigidBody::new(
+            ShapeType::Plane(Plane::new(Vector3::y_axis(), 0.0)),
+            0.0,
+        ));
+        let mut platform = RigidBody::new(
+            ShapeType::Cube(Cube::new(Vector3::new(2.0, 0.25, 2.0))),
+            1.0,
+        );
+        platform.set_body_type(BodyType::Kinematic);
+        platform.set_friction(1.0);
+        platform.set_position(&Isometry3::translation(0.0, 1.0, 0.0));
+        let platform = world.insert(platform);
+        let mut crate_ =
+            RigidBody::new(ShapeType::Cube(Cube::new(Vector3::new(0.5, 0.5, 0.5))), 1.0);
+        crate_.set_position(&Isometry3::translation(0.0, 1.75, 0.0));
+        crate_.set_elasticity(0.0);
+        crate_.set_friction(1.0);
+        let crate_ = world.insert(crate_);
+
+        // the crate falls asleep on the platform, which ignores gravity and the ground.
+        for _ in 0..120 {
+            world.update(1.0 / 60.0);
+        }
+        assert!(world.get(crate_).unwrap().is_sleeping());
+        let body = world.get(platform).unwrap();
+        assert_eq!(body.get_position().translation.y, 1.0);
+        assert_eq!(body.get_inv_mass(), 0.0);
+        assert!(!body.is_sleeping());
+
+        // an elevator, rising to its target in a second.
+        for i in 1..=60 {
+            let target = Isometry3::translation(0.0, 1.0 + i as Float / 60.0, 0.0);
+            world
+                .get_mut(platform)
+                .unwrap()
+                .set_kinematic_target(&target);
+            world.update(1.0 / 60.0);
+        }
+        let body = world.get(platform).unwrap();
+        assert!((body.get_position().translation.y - 2.0).abs() < 1.0e-5);
+        assert!((body.get_linear_velocity().y - 1.0).abs() < 1.0e-3);
+        let body = world.get(crate_).unwrap();
+        assert!(!body.is_sleeping());
+        assert!((body.get_position().translation.y - 2.75).abs() < 0.05);
+
+        // a moving platform, carrying the crate along by friction.
+        let body = world.get_mut(platform).unwrap();
+        body.clear_kinematic_target();
+        body.set_linear_velocity(&Vector3::new(1.0, 0.0, 0.0));
+        for _ in 0..60 {
+            world.update(1.0 / 60.0);
+        }
+        let body = world.get(platform).unwrap();
+        assert!((body.get_position().translation.x - 1.0).abs() < 1.0e-3);
+        let body = world.get(crate_).unwrap();
+        assert!((body.get_linear_velocity().x - 1.0).abs() < 0.1);
+        assert!(body.get_position().translation.x > 0.5);
+    }
+
+    #[test]
+    fn test_set_inv_mass() {
+        let mut world = PhysicsWorld::new();
+        let cube = || ShapeType::Cube(Cube::new(Vector3::new(0.5, 0.5, 0.5)));
+        // a body made static, then given a mass, falls.
+        let mut falling = RigidBody::new(cube(), 0.0);
+        falling.set_inv_mass(1.0);
+        assert_eq!(falling.get_body_type(), BodyType::Dynamic);
+        let falling = world.insert(falling);
+        // a dynamic body without mass is static, and stays in the air.
+        let mut fixed = RigidBody::new(cube(), 1.0);
+        fixed.set_position(&Isometry3::translation(5.0, 0.0, 0.0));
+        fixed.set_inv_mass(0.0);
+        assert_eq!(fixed.get_body_type(), BodyType::Static);
+        let fixed = world.insert(fixed);
+        // kinematic bodies stay kinematic.
+        let mut kinematic = RigidBody::new(cube(), 1.0);
+        kinematic.set_body_type(BodyType::Kinematic);
+        kinematic.set_inv_mass(0.0);
+        assert_eq!(kinematic.get_body_type(), BodyType::Kinematic);
+
+        for _ in 0..60 {
+            world.update(1.0 / 60.0);
+        }
+        assert!(world.get(falling).unwrap().get_position().translation.y < -4.0);
+        let body = world.get(fixed).unwrap();
+        assert_eq!(body.get_position().translation.y, 0.0);
+        assert!(!body.is_sleeping());
+    }
+
+    #[test]
+    fn test_materials() {
+        // a ramp too steep for the dynamic friction of the boxes, but not their static one.
+        let mut world = PhysicsWorld::new();
+        let angle = PI / 9.0;
+        let ramp = Isometry3::new(Vector3::zeros(), Vector3::new(0.0, 0.0, angle));
+        let mut body = RigidBody::new(ShapeType::Cube(Cube::new(Vector3::new(5.0, 0.5, 5.0))), 0.0);
+        body.set_position(&ramp);
+        world.insert(body);
+        let material = PhysicsMaterial {
+            static_friction: 0.45,
+            dynamic_friction: 0.2,
+            restitution: 0.0,
+            friction_combine: CombineMode::Max,
+            restitution_combine: CombineMode::Min,
+            ..PhysicsMaterial::default()
+        };
+        let downhill = ramp.rotation * -Vector3::x();
+        let mut insert_box = |z: Float, speed: Float| {
+            let mut body =
+                RigidBody::new(ShapeType::Cube(Cube::new(Vector3::new(0.5, 0.5, 0.5))), 1.0);
+            body.set_material(&material);
+            body.set_position(&(ramp * Isometry3::translation(0.0, 1.0, z)));
+            body.set_linear_velocity(&(downhill * speed));
+            world.insert(body)
+        };
+        let resting = insert_box(-2.0, 0.0);
+        let sliding = insert_box(2.0, 1.0);
+        for _ in 0..60 {
+            world.update(1.0 / 60.0);
+        }
+        let body = world.get(resting).unwrap();
+        let moved =
+            body.get_position().translation.vector - (ramp * Point3::new(0.0, 1.0, -2.0)).coords;
+        assert!(moved.magnitude() < 0.01);
+        let body = world.get(sliding).unwrap();
+        assert!(body.get_linear_velocity().dot(&downhill) > 1.5);
+
+        // balls dropped on the ground, and rolling on it.
+        let mut world = PhysicsWorld::new();
+        world.insert(RigidBody::new(
+            ShapeType::Plane(Plane::new(Vector3::y_axis(), 0.0)),
+            0.0,
+        ));
+        let mut insert_ball = |x: Float, material: PhysicsMaterial, speed: Float| {
+            let mut body = RigidBody::new(ShapeType::Sphere(Sphere::new(0.5)), 1.0);
+            body.set_material(&material);
+            body.set_position(&Isometry3::translation(x, 3.0, 0.0));
+            body.set_linear_velocity(&Vector3::new(0.0, 0.0, speed));
+            body.set_angular_velocity(&Vector3::new(speed / 0.5, 0.0, 0.0));
+            world.insert(body)
+        };
+        let bouncy = PhysicsMaterial {
+            restitution: 0.9,
+            restitution_combine: CombineMode::Max,
+            ..PhysicsMaterial::default()
+        };
+        let rolling = PhysicsMaterial {
+            restitution: 0.0,
+            rolling_resistance: 0.05,
+            restitution_combine: CombineMode::Min,
+            ..PhysicsMaterial::default()
+        };
+        let free = PhysicsMaterial {
+            rolling_resistance: 0.0,
+            ..rolling
+        };
+        let rubber = insert_ball(-3.0, bouncy, 0.0);
+        let clay = insert_ball(0.0, free, 3.0);
+        let rolled = insert_ball(3.0, rolling, 3.0);
+        let (mut rubber_peak, mut clay_peak) = (0.0, 0.0);
+        for i in 0..120 {
+            world.update(1.0 / 60.0);
+            if i > 60 {
+                let height = |id| world.get(id).unwrap().get_position().translation.y;
+                rubber_peak = height(rubber).max(rubber_peak);
+                clay_peak = height(clay).max(clay_peak);
+            }
+        }
+        assert!(rubber_peak > 1.5);
+        assert!(clay_peak < 0.6);
+        let speed = |id| world.get(id).unwrap().get_linear_velocity().z;
+        assert!(speed(rolled) < speed(clay) - 0.5);
+        assert!(speed(rolled) > 0.0);
+    }
 }
@@ -5,6 +5,7 @@ pub mod scene;
 pub mod model;
 pub mod entity;
 mod ball_generator;
+mod capsule_generator;
 mod texture;
 mod render_pass;
 mod device;
@@ -0,0 +1,45 @@
+use crate::math::*;
+use crate::renderer::ModelVertex;
+/// Generates a capsule along the y axis.
+/// The upper hemisphere uses the first 6 rings and the lower one uses the rest.
+pub fn generate_capsule(radius: f32, half_height: f32) -> (Vec<ModelVertex>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    for i in 0..12 {
+        let (phi, offset) = if i < 6 {
+            (i as f32 * PI / 10.0, half_height)
+        } else {
+            ((i - 1) as f32 * PI / 10.0, -half_height)
+        };
+        for j in 0..16 {
+            let theta = 0.0 + j as f32 * 2.0 * PI / 15.0;
+            let x = phi.sin() * theta.cos();
+            let y = phi.cos();
+            let z = phi.sin() * theta.sin();
+            let tex_coords = [j as f32 / 15.0, i as f32 / 11.0];
+            let pos = [x * radius, y * radius + offset, z * radius];
+            let normal = [x, y, z];
+            let vertex = ModelVertex {
+                pos,
+                tex_coords,
+                normal,
+            };
+            vertices.push(vertex);
+        }
+    }
+    let mut indices = Vec::new();
+    for i in 0..11 {
+        for j in 0..15 {
+            let a = 16 * i + j;
+            let b = a + 16;
+            let c = b + 1;
+            let d = a + 1;
+            indices.push(a);
+            indices.push(c);
+            indices.push(b);
+            indices.push(d);
+            indices.push(c);
+            indices.push(a);
+        }
+    }
+    (vertices, indices)
+}
@@ -64,6 +64,15 @@ impl ResourceManager{
         Ok(Arc::new(Model{name: "ball".into(), meshes, materials}))
     }
 
+    pub async fn get_capsule_model(&mut self, material_file: &str, radius: f32, half_height: f32) -> anyhow::Result<Arc<Model>>{
+        let (vertices, indices) = crate::renderer::capsule_generator::generate_capsule(radius, half_height);
+        let meshes = vec![model::Mesh::new_with_index_u32(self.device.as_ref(), "capsule".into(), &vertices, &indices, 0)];
+        let material_json = load_string(material_file).await?;
+        let material_data: MaterialData = serde_json::from_str(&material_json)?;
+        let materials = vec![self.create_material_from_data(material_data).await?];
+        Ok(Arc::new(Model{name: "capsule".into(), meshes, materials}))
+    }
+
     pub async fn get_model_json(&mut self, file_name: &str) -> anyhow::Result<Arc<Model>>{
         if let Some(m) = self.models.get(file_name){
             return Ok(m.clone());